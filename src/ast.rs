@@ -1,27 +1,605 @@
+//! Trees of expressions and statements, as the parser builds them.
+//!
+//! A tree can nest far deeper than the call stack allows, a chain like
+//! `1 + 1 + ... + 1` nests once for every operator. So cloning, comparing,
+//! printing and dropping a tree, and every other traversal in the crate but
+//! the `visit` passes, keep their pending work in a stack on the heap instead
+//! of recursing.
+
+use std::mem;
+use std::rc::Rc;
+
 use crate::token::Token;
 
+/// Operators between two operands, `a + b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    // comparisons, evaluate to 1 if true otherwise 0.
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+/// Operators before their operand, `-a`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UnaryOperator {
+    Plus,
+    Negate,
+    Root,
+}
+
+/// Operators after their operand, `a!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PostfixOperator {
+    Factorial,
+}
+
+// every operator, in the order they are declared.
+// the binary format encodes operators as their position here, only ever append.
+pub(crate) const BINARY_OPERATORS: [BinaryOperator; 11] = [
+    BinaryOperator::Add,
+    BinaryOperator::Subtract,
+    BinaryOperator::Multiply,
+    BinaryOperator::Divide,
+    BinaryOperator::Power,
+    BinaryOperator::Less,
+    BinaryOperator::LessEqual,
+    BinaryOperator::Greater,
+    BinaryOperator::GreaterEqual,
+    BinaryOperator::Equal,
+    BinaryOperator::NotEqual,
+];
+
+pub(crate) const UNARY_OPERATORS: [UnaryOperator; 3] = [
+    UnaryOperator::Plus,
+    UnaryOperator::Negate,
+    UnaryOperator::Root,
+];
+
+pub(crate) const POSTFIX_OPERATORS: [PostfixOperator; 1] = [PostfixOperator::Factorial];
+
 /// Enum for modeling AST nodes.
+/// Trees compare equal when they have the same shape, names and values.
 pub enum AST {
-    // each node.
-    Node(Token),
-    // connections.
-    Con(Token, Vec<AST>),
+    // number literals, `2.5`.
+    Literal(f64),
+    // variables, `x`.
+    Var(String),
+    // unary expressions, `-x`.
+    Unary {
+        op: UnaryOperator,
+        operand: Box<AST>,
+    },
+    // postfix expressions, `x!`.
+    Postfix {
+        op: PostfixOperator,
+        operand: Box<AST>,
+    },
+    // binary expressions, `x + y`.
+    Binary {
+        op: BinaryOperator,
+        lhs: Box<AST>,
+        rhs: Box<AST>,
+    },
+    // function calls, `f(1, 2)`.
+    Call(String, Vec<AST>),
+    // let bindings, `let x = 1 in x + 1`.
+    Let(String, Box<AST>, Box<AST>),
+    // anonymous functions, `(x, y) => x * y`.
+    Lambda(Vec<String>, Rc<AST>),
+    // list literals, `[1, 2, 3]`.
+    List(Vec<AST>),
+}
+
+/// Enum for modeling statements of a program.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Statement {
+    // function definitions, `f(x) = x + 1`.
+    Function(String, Vec<String>, Rc<AST>),
+    // variable assignments, `x = 3`.
+    Assignment(String, AST),
+    // expressions to evaluate.
+    Expression(AST),
+}
+
+impl BinaryOperator {
+    /// Gets the binary operator a token stands for.
+    /// # Arguments
+    /// * token - the operator token.
+    /// # Returns
+    /// * BinaryOperator - the operator, None if the token is not a binary operator.
+    pub fn from_token(token: &Token) -> Option<Self> {
+        let operator = match token {
+            Token::Plus => BinaryOperator::Add,
+            Token::Minus => BinaryOperator::Subtract,
+            Token::Star => BinaryOperator::Multiply,
+            Token::Slash => BinaryOperator::Divide,
+            Token::Caret => BinaryOperator::Power,
+            Token::Less => BinaryOperator::Less,
+            Token::LessEqual => BinaryOperator::LessEqual,
+            Token::Greater => BinaryOperator::Greater,
+            Token::GreaterEqual => BinaryOperator::GreaterEqual,
+            Token::EqualEqual => BinaryOperator::Equal,
+            Token::BangEqual => BinaryOperator::NotEqual,
+            _ => return None,
+        };
+
+        Some(operator)
+    }
+
+    /// Returns the symbol of the operator, as it is written in expressions.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Power => "^",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+        }
+    }
+
+    /// Gets the infix binding power of the operator, shared by the parser and the printer.
+    /// # Returns
+    /// * (left, right) - left and right infix binding power of the operator.
+    pub fn binding_power(self) -> (u8, u8) {
+        match self {
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual
+            | BinaryOperator::Equal
+            | BinaryOperator::NotEqual => (1, 2),
+            BinaryOperator::Add => (3, 4),
+            BinaryOperator::Subtract => (3, 4),
+            BinaryOperator::Multiply => (5, 6),
+            BinaryOperator::Divide => (5, 6),
+            // right associative, and binds tighter than unary minus.
+            BinaryOperator::Power => (10, 9),
+        }
+    }
+}
+
+impl UnaryOperator {
+    /// Gets the unary operator a token stands for.
+    /// # Arguments
+    /// * token - the operator token.
+    /// # Returns
+    /// * UnaryOperator - the operator, None if the token is not a unary operator.
+    pub fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Plus => Some(UnaryOperator::Plus),
+            Token::Minus => Some(UnaryOperator::Negate),
+            Token::Root => Some(UnaryOperator::Root),
+            _ => None,
+        }
+    }
+
+    /// Returns the symbol of the operator, as it is written in expressions.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOperator::Plus => "+",
+            UnaryOperator::Negate => "-",
+            UnaryOperator::Root => "√",
+        }
+    }
+
+    /// Gets the prefix binding power of the operator.
+    /// # Returns
+    /// * ((), right) - right prefix binding power of the operator.
+    pub fn binding_power(self) -> ((), u8) {
+        match self {
+            UnaryOperator::Plus | UnaryOperator::Negate | UnaryOperator::Root => ((), 7),
+        }
+    }
+}
+
+impl PostfixOperator {
+    /// Gets the postfix operator a token stands for.
+    /// # Arguments
+    /// * token - the operator token.
+    /// # Returns
+    /// * PostfixOperator - the operator, None if the token is not a postfix operator.
+    pub fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Bang => Some(PostfixOperator::Factorial),
+            _ => None,
+        }
+    }
+
+    /// Returns the symbol of the operator, as it is written in expressions.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            PostfixOperator::Factorial => "!",
+        }
+    }
+
+    /// Gets the postfix binding power of the operator.
+    /// # Returns
+    /// * (left, ()) - left postfix binding power of the operator.
+    pub fn binding_power(self) -> (u8, ()) {
+        match self {
+            PostfixOperator::Factorial => (11, ()),
+        }
+    }
+}
+
+impl AST {
+    /// Creates a unary expression.
+    pub fn unary(op: UnaryOperator, operand: AST) -> Self {
+        AST::Unary {
+            op,
+            operand: Box::new(operand),
+        }
+    }
+
+    /// Creates a postfix expression.
+    pub fn postfix(op: PostfixOperator, operand: AST) -> Self {
+        AST::Postfix {
+            op,
+            operand: Box::new(operand),
+        }
+    }
+
+    /// Creates a binary expression.
+    pub fn binary(op: BinaryOperator, lhs: AST, rhs: AST) -> Self {
+        AST::Binary {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    /// Moves the children of this node to the stack, leaving the node without any.
+    /// # Arguments
+    /// * stack - where to move the children.
+    fn take_children(&mut self, stack: &mut Vec<AST>) {
+        // a literal stands in for the moved children, it doesn't allocate.
+        let mut take =
+            |child: &mut Box<AST>| stack.push(mem::replace(child.as_mut(), AST::Literal(0.0)));
+
+        match self {
+            AST::Literal(_) | AST::Var(_) => {}
+            AST::Unary { operand, .. } | AST::Postfix { operand, .. } => take(operand),
+            AST::Binary { lhs, rhs, .. } | AST::Let(_, lhs, rhs) => {
+                take(lhs);
+                take(rhs);
+            }
+            AST::Call(_, children) | AST::List(children) => stack.append(children),
+            // a shared body is still used by someone else, it is dropped with its last owner.
+            AST::Lambda(_, body) => {
+                if let Some(body) = Rc::get_mut(body) {
+                    stack.push(mem::replace(body, AST::Literal(0.0)));
+                }
+            }
+        }
+    }
+}
+
+/// clones the tree node by node, bodies of lambdas are shared like `Rc` does.
+impl Clone for AST {
+    fn clone(&self) -> Self {
+        // every node is copied with literals standing in for its children,
+        // which are copied over them in turn.
+        let mut root = AST::Literal(0.0);
+        let mut pending = vec![(&mut root, self)];
+        let placeholders = |count: usize| (0..count).map(|_| AST::Literal(0.0)).collect();
+
+        while let Some((target, source)) = pending.pop() {
+            *target = match source {
+                AST::Literal(n) => AST::Literal(*n),
+                AST::Var(name) => AST::Var(name.clone()),
+                AST::Unary { op, .. } => AST::unary(*op, AST::Literal(0.0)),
+                AST::Postfix { op, .. } => AST::postfix(*op, AST::Literal(0.0)),
+                AST::Binary { op, .. } => AST::binary(*op, AST::Literal(0.0), AST::Literal(0.0)),
+                AST::Call(name, arguments) => {
+                    AST::Call(name.clone(), placeholders(arguments.len()))
+                }
+                AST::Let(name, ..) => AST::Let(
+                    name.clone(),
+                    Box::new(AST::Literal(0.0)),
+                    Box::new(AST::Literal(0.0)),
+                ),
+                AST::Lambda(parameters, body) => AST::Lambda(parameters.clone(), Rc::clone(body)),
+                AST::List(items) => AST::List(placeholders(items.len())),
+            };
+
+            match (target, source) {
+                (AST::Unary { operand, .. }, AST::Unary { operand: from, .. })
+                | (AST::Postfix { operand, .. }, AST::Postfix { operand: from, .. }) => {
+                    pending.push((operand.as_mut(), from.as_ref()))
+                }
+                (AST::Binary { lhs, rhs, .. }, AST::Binary { lhs: a, rhs: b, .. })
+                | (AST::Let(_, lhs, rhs), AST::Let(_, a, b)) => {
+                    pending.push((lhs.as_mut(), a.as_ref()));
+                    pending.push((rhs.as_mut(), b.as_ref()));
+                }
+                (AST::Call(_, children), AST::Call(_, from))
+                | (AST::List(children), AST::List(from)) => {
+                    pending.extend(children.iter_mut().zip(from))
+                }
+                _ => {}
+            }
+        }
+
+        root
+    }
+}
+
+/// drops the tree one node at a time.
+impl Drop for AST {
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        self.take_children(&mut stack);
+
+        // every node is dropped after its children are taken,
+        // so dropping it doesn't recurse.
+        while let Some(mut node) = stack.pop() {
+            node.take_children(&mut stack);
+        }
+    }
+}
+
+/// compares trees node by node.
+impl PartialEq for AST {
+    fn eq(&self, other: &Self) -> bool {
+        let mut pending = vec![(self, other)];
+
+        while let Some(pair) = pending.pop() {
+            match pair {
+                (AST::Literal(a), AST::Literal(b)) if a == b => {}
+                (AST::Var(a), AST::Var(b)) if a == b => {}
+                (
+                    AST::Unary { op, operand },
+                    AST::Unary {
+                        op: other,
+                        operand: b,
+                    },
+                ) if op == other => pending.push((operand, b)),
+                (
+                    AST::Postfix { op, operand },
+                    AST::Postfix {
+                        op: other,
+                        operand: b,
+                    },
+                ) if op == other => pending.push((operand, b)),
+                (
+                    AST::Binary { op, lhs, rhs },
+                    AST::Binary {
+                        op: other,
+                        lhs: a,
+                        rhs: b,
+                    },
+                ) if op == other => {
+                    pending.push((lhs, a));
+                    pending.push((rhs, b));
+                }
+                (AST::Call(name, arguments), AST::Call(other, a))
+                    if name == other && arguments.len() == a.len() =>
+                {
+                    pending.extend(arguments.iter().zip(a));
+                }
+                (AST::Let(name, value, body), AST::Let(other, a, b)) if name == other => {
+                    pending.push((value, a));
+                    pending.push((body, b));
+                }
+                (AST::Lambda(parameters, body), AST::Lambda(other, b)) if parameters == other => {
+                    pending.push((body, b))
+                }
+                (AST::List(items), AST::List(other)) if items.len() == other.len() => {
+                    pending.extend(items.iter().zip(other));
+                }
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+/// fmt display for binary operators.
+impl core::fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, " {} ", self.symbol())
+    }
+}
+
+/// fmt display for unary operators.
+impl core::fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, " {} ", self.symbol())
+    }
 }
 
-/// fmt display for ast
-/// recursively prints AST.
+/// fmt display for postfix operators.
+impl core::fmt::Display for PostfixOperator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, " {} ", self.symbol())
+    }
+}
+
+// what is left to print of an ast.
+enum Pending<'t> {
+    Ast(&'t AST),
+    Text(&'static str),
+}
+
+// pushes children to print, separated by commas.
+fn push_separated<'t>(pending: &mut Vec<Pending<'t>>, children: &'t [AST]) {
+    for (i, child) in children.iter().enumerate().rev() {
+        pending.push(Pending::Ast(child));
+        if i > 0 {
+            pending.push(Pending::Text(", "));
+        }
+    }
+}
+
+/// fmt display for ast, infinite literals print as `#inf` and `-#inf`
+/// and the ones which aren't a number as `#nan`.
 impl core::fmt::Display for AST {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            AST::Node(token) => write!(f, " {}", token),
-            AST::Con(head, rest) => {
-                write!(f, "({}", head)?;
-                for node in rest {
-                    write!(f, "{}", node)?;
+        // pieces are pushed in reverse, so they are printed in order.
+        let mut pending = vec![Pending::Ast(self)];
+
+        while let Some(piece) = pending.pop() {
+            let ast = match piece {
+                Pending::Text(text) => {
+                    write!(f, "{}", text)?;
+                    continue;
+                }
+                Pending::Ast(ast) => ast,
+            };
+
+            match ast {
+                // so they can't be mistaken for names.
+                AST::Literal(n) if n.is_nan() => write!(f, "  #nan ")?,
+                AST::Literal(n) if n.is_infinite() => {
+                    write!(f, "  {}#inf ", if *n < 0.0 { "-" } else { "" })?
+                }
+                AST::Literal(n) => write!(f, "  {} ", n)?,
+                AST::Var(name) => write!(f, "  {} ", name)?,
+                AST::Unary { op, operand } => {
+                    write!(f, "({}", op)?;
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(operand));
+                }
+                AST::Postfix { op, operand } => {
+                    write!(f, "({}", op)?;
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(operand));
+                }
+                AST::Binary { op, lhs, rhs } => {
+                    write!(f, "({}", op)?;
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(rhs));
+                    pending.push(Pending::Ast(lhs));
+                }
+                AST::Call(name, arguments) => {
+                    write!(f, "( {} ", name)?;
+                    pending.push(Pending::Text(")"));
+                    pending.extend(arguments.iter().rev().map(Pending::Ast));
+                }
+                AST::Let(name, value, body) => {
+                    write!(f, "( let  {} ", name)?;
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(body));
+                    pending.push(Pending::Ast(value));
                 }
+                AST::Lambda(parameters, body) => {
+                    write!(f, "( => ")?;
+                    for parameter in parameters {
+                        write!(f, " {} ", parameter)?;
+                    }
 
-                write!(f, ")")
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(body));
+                }
+                AST::List(items) => {
+                    write!(f, "[")?;
+                    pending.push(Pending::Text(" ]"));
+                    pending.extend(items.iter().rev().map(Pending::Ast));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// fmt debug for ast, written like the derived implementation but always on one line.
+impl core::fmt::Debug for AST {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // pieces are pushed in reverse, so they are written in order.
+        let mut pending = vec![Pending::Ast(self)];
+
+        while let Some(piece) = pending.pop() {
+            let ast = match piece {
+                Pending::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+                Pending::Ast(ast) => ast,
+            };
+
+            match ast {
+                AST::Literal(n) => write!(f, "Literal({n:?})")?,
+                AST::Var(name) => write!(f, "Var({name:?})")?,
+                AST::Unary { op, operand } => {
+                    write!(f, "Unary {{ op: {op:?}, operand: ")?;
+                    pending.push(Pending::Text(" }"));
+                    pending.push(Pending::Ast(operand));
+                }
+                AST::Postfix { op, operand } => {
+                    write!(f, "Postfix {{ op: {op:?}, operand: ")?;
+                    pending.push(Pending::Text(" }"));
+                    pending.push(Pending::Ast(operand));
+                }
+                AST::Binary { op, lhs, rhs } => {
+                    write!(f, "Binary {{ op: {op:?}, lhs: ")?;
+                    pending.push(Pending::Text(" }"));
+                    pending.push(Pending::Ast(rhs));
+                    pending.push(Pending::Text(", rhs: "));
+                    pending.push(Pending::Ast(lhs));
+                }
+                AST::Call(name, arguments) => {
+                    write!(f, "Call({name:?}, [")?;
+                    pending.push(Pending::Text("])"));
+                    push_separated(&mut pending, arguments);
+                }
+                AST::Let(name, value, body) => {
+                    write!(f, "Let({name:?}, ")?;
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(body));
+                    pending.push(Pending::Text(", "));
+                    pending.push(Pending::Ast(value));
+                }
+                AST::Lambda(parameters, body) => {
+                    write!(f, "Lambda({parameters:?}, ")?;
+                    pending.push(Pending::Text(")"));
+                    pending.push(Pending::Ast(body));
+                }
+                AST::List(items) => {
+                    write!(f, "List([")?;
+                    pending.push(Pending::Text("])"));
+                    push_separated(&mut pending, items);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// fmt display for statements.
+impl core::fmt::Display for Statement {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Statement::Function(name, parameters, body) => {
+                write!(f, "( = {}({}) {})", name, parameters.join(", "), body)
             }
+            Statement::Assignment(name, value) => write!(f, "( = {} {})", name, value),
+            Statement::Expression(ast) => write!(f, "{}", ast),
         }
     }
 }
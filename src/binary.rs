@@ -0,0 +1,442 @@
+//! A compact, versioned binary encoding of trees, so parsed expressions can
+//! be cached and loaded without parsing them again.
+//!
+//! An encoded tree is a header followed by a payload, integers are little endian.
+//!
+//! | Bytes | Contents                                            |
+//! |-------|-----------------------------------------------------|
+//! | 4     | the magic, `EXPR`                                   |
+//! | 1     | the format version, `FORMAT_VERSION`                |
+//! | 4     | length of the payload in bytes                      |
+//! | 4     | CRC-32 of the payload, catches corrupted blobs      |
+//! | ...   | the payload                                         |
+//!
+//! The payload is the instructions building the tree in postfix order,
+//! children before their parent, like the JSON form of the `serialize` module.
+//! Each instruction is a tag byte followed by its data, names are a length
+//! followed by UTF-8 and operators are a byte.
+//!
+//! | Tag | Data                                  | Builds                                         |
+//! |-----|---------------------------------------|------------------------------------------------|
+//! | 0   | f64                                   | a number                                       |
+//! | 1   | name                                  | a variable                                     |
+//! | 2   | operator                              | a unary expression of the last node            |
+//! | 3   | operator                              | a postfix expression of the last node          |
+//! | 4   | operator                              | a binary expression of the last two nodes      |
+//! | 5   | name, u32 count                       | a call with the last `count` nodes             |
+//! | 6   | name                                  | a let binding, the last two nodes are value and body |
+//! | 7   | u32 count, `count` names              | an anonymous function with the last node as body |
+//! | 8   | u32 count                             | a list of the last `count` nodes               |
+
+use std::rc::Rc;
+
+use crate::ast::{
+    BinaryOperator, PostfixOperator, UnaryOperator, AST, BINARY_OPERATORS, POSTFIX_OPERATORS,
+    UNARY_OPERATORS,
+};
+
+/// Bytes every encoded tree starts with.
+pub const MAGIC: [u8; 4] = *b"EXPR";
+
+/// Version of the format written by `encode`, `decode` only reads this one.
+pub const FORMAT_VERSION: u8 = 1;
+
+// magic, version, payload length and checksum.
+const HEADER_LENGTH: usize = 13;
+
+// a step building a tree, see the module documentation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub(crate) enum Instruction {
+    Literal(f64),
+    Var(String),
+    Unary(UnaryOperator),
+    Postfix(PostfixOperator),
+    Binary(BinaryOperator),
+    Call { name: String, arguments: usize },
+    Let(String),
+    Lambda(Vec<String>),
+    List(usize),
+}
+
+/// Encodes a tree, see the module documentation for the format.
+/// # Arguments
+/// * ast - the tree.
+/// # Returns
+/// * `Result<Vec<u8>, String>` - the encoded tree, otherwise an error if a name,
+///   a list or the whole payload is too long for the u32 lengths of the format.
+/// # Examples
+/// ```
+/// use expr_solver::{binary, parse_expression};
+///
+/// let ast = parse_expression("2 * x + 1").unwrap();
+/// let bytes = binary::encode(&ast).unwrap();
+/// assert!(bytes.starts_with(b"EXPR"));
+/// assert_eq!(binary::decode(&bytes), Ok(ast));
+/// ```
+pub fn encode(ast: &AST) -> Result<Vec<u8>, String> {
+    let mut payload = vec![];
+    for instruction in instructions(ast) {
+        match instruction {
+            Instruction::Literal(n) => {
+                payload.push(0);
+                payload.extend(n.to_le_bytes());
+            }
+            Instruction::Var(name) => {
+                payload.push(1);
+                write_name(&mut payload, &name)?;
+            }
+            Instruction::Unary(op) => {
+                payload.push(2);
+                payload.push(code(&UNARY_OPERATORS, op));
+            }
+            Instruction::Postfix(op) => {
+                payload.push(3);
+                payload.push(code(&POSTFIX_OPERATORS, op));
+            }
+            Instruction::Binary(op) => {
+                payload.push(4);
+                payload.push(code(&BINARY_OPERATORS, op));
+            }
+            Instruction::Call { name, arguments } => {
+                payload.push(5);
+                write_name(&mut payload, &name)?;
+                write_count(&mut payload, arguments)?;
+            }
+            Instruction::Let(name) => {
+                payload.push(6);
+                write_name(&mut payload, &name)?;
+            }
+            Instruction::Lambda(parameters) => {
+                payload.push(7);
+                write_count(&mut payload, parameters.len())?;
+                for parameter in &parameters {
+                    write_name(&mut payload, parameter)?;
+                }
+            }
+            Instruction::List(count) => {
+                payload.push(8);
+                write_count(&mut payload, count)?;
+            }
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    bytes.extend(MAGIC);
+    bytes.push(FORMAT_VERSION);
+    write_count(&mut bytes, payload.len())?;
+    bytes.extend(crc32(&payload).to_le_bytes());
+    bytes.extend(payload);
+    Ok(bytes)
+}
+
+/// Decodes a tree written by `encode`, validating the whole blob first:
+/// the magic, the version, the length and the checksum of the payload,
+/// and that its instructions build exactly one tree.
+/// # Arguments
+/// * bytes - the encoded tree.
+/// # Returns
+/// * AST - the tree, or an error saying why the blob can't be used.
+/// # Examples
+/// ```
+/// use expr_solver::binary;
+///
+/// assert_eq!(
+///     binary::decode(b"2 + 2"),
+///     Err("Not an encoded expression, it doesn't start with 'EXPR'.".to_string())
+/// );
+/// ```
+pub fn decode(bytes: &[u8]) -> Result<AST, String> {
+    if !bytes.starts_with(&MAGIC) {
+        return Err("Not an encoded expression, it doesn't start with 'EXPR'.".to_string());
+    }
+    if bytes.len() < HEADER_LENGTH {
+        return Err("Encoded expression is truncated, the header is incomplete.".to_string());
+    }
+
+    let version = bytes[4];
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported format version {version}, expected {FORMAT_VERSION}."
+        ));
+    }
+
+    let mut header = Cursor {
+        bytes: &bytes[5..HEADER_LENGTH],
+        position: 0,
+    };
+    let length = header.count()?;
+    let checksum = u32::from_le_bytes(header.array()?);
+
+    let payload = &bytes[HEADER_LENGTH..];
+    if payload.len() != length {
+        return Err(format!(
+            "Encoded expression is truncated or padded, expected {length} byte(s) of payload, found {}.",
+            payload.len()
+        ));
+    }
+    if crc32(payload) != checksum {
+        return Err("Encoded expression is corrupted, its checksum doesn't match.".to_string());
+    }
+
+    let mut cursor = Cursor {
+        bytes: payload,
+        position: 0,
+    };
+    let mut instructions = vec![];
+    while !cursor.is_empty() {
+        instructions.push(cursor.instruction()?);
+    }
+
+    build(instructions)
+}
+
+/// Flattens a tree into instructions in postfix order, using a stack on the
+/// heap instead of recursion.
+/// # Arguments
+/// * ast - the tree.
+/// # Returns
+/// * `Vec<Instruction>` - instructions building the tree.
+pub(crate) fn instructions(ast: &AST) -> Vec<Instruction> {
+    let mut instructions = vec![];
+
+    // nodes are visited twice, their children are pushed the first time
+    // and the node is written the second time, after all of them.
+    let mut pending = vec![(ast, false)];
+    while let Some((ast, visited)) = pending.pop() {
+        if !visited {
+            pending.push((ast, true));
+            match ast {
+                AST::Literal(_) | AST::Var(_) => {}
+                AST::Unary { operand, .. } | AST::Postfix { operand, .. } => {
+                    pending.push((operand, false))
+                }
+                AST::Binary { lhs, rhs, .. } | AST::Let(_, lhs, rhs) => {
+                    pending.push((rhs, false));
+                    pending.push((lhs, false));
+                }
+                AST::Call(_, children) | AST::List(children) => {
+                    pending.extend(children.iter().rev().map(|child| (child, false)))
+                }
+                AST::Lambda(_, body) => pending.push((body, false)),
+            }
+            continue;
+        }
+
+        instructions.push(match ast {
+            AST::Literal(n) => Instruction::Literal(*n),
+            AST::Var(name) => Instruction::Var(name.clone()),
+            AST::Unary { op, .. } => Instruction::Unary(*op),
+            AST::Postfix { op, .. } => Instruction::Postfix(*op),
+            AST::Binary { op, .. } => Instruction::Binary(*op),
+            AST::Call(name, arguments) => Instruction::Call {
+                name: name.clone(),
+                arguments: arguments.len(),
+            },
+            AST::Let(name, _, _) => Instruction::Let(name.clone()),
+            AST::Lambda(parameters, _) => Instruction::Lambda(parameters.clone()),
+            AST::List(items) => Instruction::List(items.len()),
+        });
+    }
+
+    instructions
+}
+
+/// Builds a tree out of instructions in postfix order.
+/// # Arguments
+/// * instructions - the instructions.
+/// # Returns
+/// * AST - the tree, or an error if the instructions don't build exactly one tree.
+pub(crate) fn build(instructions: Vec<Instruction>) -> Result<AST, String> {
+    let mut nodes: Vec<AST> = vec![];
+
+    for instruction in instructions {
+        let node = match instruction {
+            Instruction::Literal(n) => AST::Literal(n),
+            Instruction::Var(name) => AST::Var(name),
+            Instruction::Unary(op) => AST::unary(op, pop(&mut nodes, 1)?.remove(0)),
+            Instruction::Postfix(op) => AST::postfix(op, pop(&mut nodes, 1)?.remove(0)),
+            Instruction::Binary(op) => {
+                let mut operands = pop(&mut nodes, 2)?;
+                let rhs = operands.remove(1);
+                AST::binary(op, operands.remove(0), rhs)
+            }
+            Instruction::Call { name, arguments } => AST::Call(name, pop(&mut nodes, arguments)?),
+            Instruction::Let(name) => {
+                let mut children = pop(&mut nodes, 2)?;
+                let body = children.remove(1);
+                AST::Let(name, Box::new(children.remove(0)), Box::new(body))
+            }
+            Instruction::Lambda(parameters) => {
+                AST::Lambda(parameters, Rc::new(pop(&mut nodes, 1)?.remove(0)))
+            }
+            Instruction::List(count) => AST::List(pop(&mut nodes, count)?),
+        };
+        nodes.push(node);
+    }
+
+    match nodes.len() {
+        1 => Ok(nodes.remove(0)),
+        count => Err(format!(
+            "Expected instructions building one tree, they build {count}."
+        )),
+    }
+}
+
+/// Pops the last nodes built, in the order they were built.
+/// # Arguments
+/// * nodes - the nodes built so far.
+/// * count - how many nodes to pop.
+fn pop(nodes: &mut Vec<AST>, count: usize) -> Result<Vec<AST>, String> {
+    if count > nodes.len() {
+        return Err(format!(
+            "Expected {count} node(s) before the instruction, found {}.",
+            nodes.len()
+        ));
+    }
+
+    Ok(nodes.split_off(nodes.len() - count))
+}
+
+/// Returns the code of an operator, its position in the list of operators.
+fn code<T: PartialEq>(operators: &[T], op: T) -> u8 {
+    // every operator is in its list.
+    operators.iter().position(|other| *other == op).unwrap_or(0) as u8
+}
+
+/// Writes a count as a u32.
+/// # Returns
+/// Result with nothing, otherwise error string if the count doesn't fit.
+fn write_count(bytes: &mut Vec<u8>, count: usize) -> Result<(), String> {
+    let count = u32::try_from(count).map_err(|_| {
+        format!(
+            "Can't encode a count of {count}, the format allows at most {}.",
+            u32::MAX
+        )
+    })?;
+    bytes.extend(count.to_le_bytes());
+    Ok(())
+}
+
+/// Writes a name as its length followed by its UTF-8 bytes.
+/// # Returns
+/// Result with nothing, otherwise error string if the name is too long.
+fn write_name(bytes: &mut Vec<u8>, name: &str) -> Result<(), String> {
+    write_count(bytes, name.len())?;
+    bytes.extend(name.as_bytes());
+    Ok(())
+}
+
+/// Computes the CRC-32 checksum of bytes, the one zip and PNG use.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// reads values out of a payload, every read checks there are enough bytes left.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    /// Reads the next instruction.
+    fn instruction(&mut self) -> Result<Instruction, String> {
+        let start = self.position;
+        let tag = self.byte()?;
+        Ok(match tag {
+            0 => Instruction::Literal(f64::from_le_bytes(self.array()?)),
+            1 => Instruction::Var(self.name()?),
+            2 => Instruction::Unary(self.operator(&UNARY_OPERATORS)?),
+            3 => Instruction::Postfix(self.operator(&POSTFIX_OPERATORS)?),
+            4 => Instruction::Binary(self.operator(&BINARY_OPERATORS)?),
+            5 => Instruction::Call {
+                name: self.name()?,
+                arguments: self.count()?,
+            },
+            6 => Instruction::Let(self.name()?),
+            7 => {
+                let count = self.count()?;
+                // the count isn't trusted to size the list, the names run out first.
+                let mut parameters = vec![];
+                for _ in 0..count {
+                    parameters.push(self.name()?);
+                }
+                Instruction::Lambda(parameters)
+            }
+            8 => Instruction::List(self.count()?),
+            _ => {
+                return Err(format!(
+                    "Unknown instruction {tag} at byte {start} of the payload."
+                ))
+            }
+        })
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        let [byte] = self.array()?;
+        Ok(byte)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self.take(N)?;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok(array)
+    }
+
+    fn count(&mut self) -> Result<usize, String> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let start = self.position;
+        let length = self.count()?;
+        match std::str::from_utf8(self.take(length)?) {
+            Ok(name) => Ok(name.to_string()),
+            Err(_) => Err(format!(
+                "Invalid UTF-8 in name at byte {start} of the payload."
+            )),
+        }
+    }
+
+    fn operator<T: Copy>(&mut self, operators: &[T]) -> Result<T, String> {
+        let start = self.position;
+        let code = self.byte()?;
+        match operators.get(code as usize) {
+            Some(op) => Ok(*op),
+            None => Err(format!(
+                "Unknown operator {code} at byte {start} of the payload."
+            )),
+        }
+    }
+
+    /// Takes the next bytes.
+    /// # Arguments
+    /// * length - how many bytes.
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.position < length {
+            return Err(format!(
+                "Expected {length} more byte(s) at byte {} of the payload, found {}.",
+                self.position,
+                self.bytes.len() - self.position
+            ));
+        }
+
+        let bytes = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::lexer::LexError;
+use crate::token::Span;
+
+/// A problem found in the source string, along with where it is.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    // description of the problem.
+    pub message: String,
+    // byte offsets of the text causing the problem.
+    pub span: Span,
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(error: LexError) -> Self {
+        Self {
+            message: error.message,
+            span: error.span,
+        }
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}..{}: {}",
+            self.span.start, self.span.end, self.message
+        )
+    }
+}
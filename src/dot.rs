@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::ast::AST;
+use crate::interpreter::Interpreter;
+use crate::observer::Observer;
+use crate::trace::Operation;
+use crate::value::Value;
+
+/// Exports an expression as a Graphviz DOT graph, each node labelled with
+/// its operator, name or number, and edges going to its operands in order.
+/// # Arguments
+/// * ast - the expression.
+/// # Returns
+/// * String - the graph, render it with `dot -Tsvg`.
+/// # Examples
+/// ```
+/// use expr_solver::{dot::to_dot, parse_expression};
+///
+/// let ast = parse_expression("2 + x").unwrap();
+/// assert_eq!(
+///     to_dot(&ast),
+///     r#"digraph AST {
+///     ordering=out;
+///     node [shape=box];
+///     n0 [label="+"];
+///     n1 [label="2"];
+///     n0 -> n1;
+///     n2 [label="x"];
+///     n0 -> n2;
+/// }
+/// "#
+/// );
+/// ```
+pub fn to_dot(ast: &AST) -> String {
+    render(ast, None)
+}
+
+/// Exports an expression as a Graphviz DOT graph like `to_dot`, with the value
+/// of every node below its label, `= 14`.
+/// The expression is evaluated once with the given interpreter, so variables
+/// and functions defined in it can be used, and its observer sees the evaluation
+/// as usual. Nodes which weren't evaluated, because the evaluation failed before
+/// reaching them or they are inside an anonymous function, are left without one.
+/// # Arguments
+/// * ast - the expression.
+/// * interpreter - evaluates the expression.
+/// # Returns
+/// * String - the graph.
+pub fn to_dot_with_values(ast: &AST, interpreter: &mut Interpreter) -> String {
+    let inner = interpreter.replace_observer(None);
+    let values = Rc::new(RefCell::new(Values {
+        values: HashMap::new(),
+        inner: inner.clone(),
+    }));
+
+    interpreter.replace_observer(Some(values.clone()));
+    // a failed evaluation still has the values of the nodes before the failure.
+    let _ = interpreter.evaluate(ast);
+    interpreter.replace_observer(inner);
+
+    let values = &values.borrow().values;
+    render(ast, Some(values))
+}
+
+/// Writes the graph.
+/// # Arguments
+/// * ast - the expression.
+/// * values - value of every evaluated node, by address, if values are shown.
+/// # Returns
+/// * String - the graph.
+fn render<'t>(ast: &'t AST, values: Option<&HashMap<*const AST, Value>>) -> String {
+    let mut out = String::from("digraph AST {\n    ordering=out;\n    node [shape=box];\n");
+
+    // nodes left to write, along with the id of their parent, the label of the edge
+    // to them and whether they are inside an anonymous function.
+    let mut pending = vec![(ast, None, false)];
+    let mut next_id = 0;
+
+    while let Some((ast, parent, in_lambda)) = pending.pop() {
+        let id = next_id;
+        next_id += 1;
+
+        // nodes inside anonymous functions have a value for every call.
+        let mut label = escape(&label(ast));
+        let value = values.and_then(|values| values.get(&(ast as *const AST)));
+        if let (Some(value), false) = (value, in_lambda) {
+            label.push_str("\\n= ");
+            label.push_str(&escape(&value.to_string()));
+        }
+
+        // writing to a string never fails.
+        let _ = writeln!(out, "    n{id} [label=\"{label}\"];");
+        match parent {
+            Some((parent, "")) => {
+                let _ = writeln!(out, "    n{parent} -> n{id};");
+            }
+            Some((parent, edge)) => {
+                let _ = writeln!(out, "    n{parent} -> n{id} [label=\"{edge}\"];");
+            }
+            None => {}
+        }
+
+        // children are pushed in reverse, so they get their ids in order.
+        let in_lambda = in_lambda || matches!(ast, AST::Lambda(..));
+        let edge = |child: &'t AST| (child, Some((id, "")), in_lambda);
+        match ast {
+            AST::Literal(_) | AST::Var(_) => {}
+            AST::Unary { operand, .. } | AST::Postfix { operand, .. } => {
+                pending.push(edge(operand))
+            }
+            AST::Binary { lhs, rhs, .. } => {
+                pending.push(edge(rhs));
+                pending.push(edge(lhs));
+            }
+            AST::Call(_, children) | AST::List(children) => {
+                pending.extend(children.iter().rev().map(edge));
+            }
+            AST::Let(_, value, body) => {
+                pending.push((body, Some((id, "body")), in_lambda));
+                pending.push((value, Some((id, "value")), in_lambda));
+            }
+            AST::Lambda(_, body) => pending.push(edge(body.as_ref())),
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+// writes down the value of every node as it is left, passing every event on
+// to the observer the interpreter already had.
+struct Values {
+    values: HashMap<*const AST, Value>,
+    inner: Option<Rc<RefCell<dyn Observer>>>,
+}
+
+impl Observer for Values {
+    fn enter(&mut self, ast: &AST) {
+        if let Some(inner) = &self.inner {
+            inner.borrow_mut().enter(ast);
+        }
+    }
+
+    fn leave(&mut self, ast: &AST, value: &Value) {
+        self.values.insert(ast, value.clone());
+        if let Some(inner) = &self.inner {
+            inner.borrow_mut().leave(ast, value);
+        }
+    }
+
+    fn apply(&mut self, ast: &AST, operation: &Operation, operands: &[Value], result: &Value) {
+        if let Some(inner) = &self.inner {
+            inner.borrow_mut().apply(ast, operation, operands, result);
+        }
+    }
+}
+
+/// Returns the label of a node, without its children.
+fn label(ast: &AST) -> String {
+    match ast {
+        AST::Literal(n) => n.to_string(),
+        AST::Var(name) => name.clone(),
+        AST::Unary { op, .. } => op.symbol().to_string(),
+        AST::Postfix { op, .. } => op.symbol().to_string(),
+        AST::Binary { op, .. } => op.symbol().to_string(),
+        AST::Call(name, _) => format!("{name}()"),
+        AST::Let(name, _, _) => format!("let {name}"),
+        AST::Lambda(parameters, _) => format!("({}) =>", parameters.join(", ")),
+        AST::List(_) => "[ ]".to_string(),
+    }
+}
+
+/// Escapes the characters with a meaning inside a quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
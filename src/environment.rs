@@ -0,0 +1,115 @@
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// Holds everything an expression can refer to by name.
+/// Parameters and let bindings are lexically scoped, a function body
+/// only sees its own parameters and what it captured, never the bindings of its caller.
+pub struct Environment {
+    // global variables, assigned values and functions defined by name.
+    globals: HashMap<String, Value>,
+    // stack of local scopes, created by calls and let bindings.
+    scopes: Vec<HashMap<String, Value>>,
+    // index of the first scope visible to the function being evaluated.
+    frame: usize,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Environment {
+    /// Constructor for environment.
+    ///
+    /// # Returns
+    /// * Environment - new environment with no user definitions.
+    pub fn new() -> Self {
+        Self {
+            globals: HashMap::new(),
+            scopes: vec![],
+            frame: 0,
+        }
+    }
+
+    /// Defines a global variable, replaces previous definition with same name.
+    /// # Arguments
+    /// * name - name of the variable.
+    /// * value - value of the variable.
+    pub fn define_variable(&mut self, name: &str, value: Value) {
+        log::trace!("[expr-solver] defining variable {}={}", name, value);
+        self.globals.insert(name.to_string(), value);
+    }
+
+    /// Looks up a variable, searching local scopes first, then global
+    /// variables and then constants.
+    /// # Arguments
+    /// * name - name of the variable.
+    /// # Returns
+    /// The value of the variable if it is visible.
+    pub fn variable(&self, name: &str) -> Option<Value> {
+        self.scopes[self.frame..]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .or_else(|| self.globals.get(name).cloned())
+            .or_else(|| constant(name).map(Value::Number))
+    }
+
+    /// Collects every local variable visible right now, used to create closures.
+    /// # Returns
+    /// Map of variable names to their values, inner scopes shadow outer ones.
+    pub fn capture(&self) -> HashMap<String, Value> {
+        let mut captured = HashMap::new();
+        for scope in &self.scopes[self.frame..] {
+            for (name, value) in scope {
+                captured.insert(name.clone(), value.clone());
+            }
+        }
+        captured
+    }
+
+    /// Opens a new scope on top of the current ones, used by let bindings.
+    /// # Arguments
+    /// * scope - variables defined in the new scope.
+    pub fn push_scope(&mut self, scope: HashMap<String, Value>) {
+        self.scopes.push(scope);
+    }
+
+    /// Closes the innermost scope.
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Opens the scope of a function call, hiding every local scope
+    /// of the caller.
+    /// # Arguments
+    /// * scope - the parameters and captured variables of the function.
+    /// # Returns
+    /// The frame of the caller, which must be passed to `exit_call`.
+    pub fn enter_call(&mut self, scope: HashMap<String, Value>) -> usize {
+        let caller = self.frame;
+        self.frame = self.scopes.len();
+        self.scopes.push(scope);
+        caller
+    }
+
+    /// Closes the scope of a function call and restores the caller's scopes.
+    /// # Arguments
+    /// * caller - frame returned by `enter_call`.
+    pub fn exit_call(&mut self, caller: usize) {
+        self.scopes.truncate(self.frame);
+        self.frame = caller;
+    }
+}
+
+/// Returns value of a builtin constant.
+/// # Arguments
+/// * name - name of the constant.
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
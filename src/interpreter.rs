@@ -1,9 +1,240 @@
-use crate::{ast::AST, token::Token, utils::factorial};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::{
+    ast::{BinaryOperator, PostfixOperator, Statement, UnaryOperator, AST},
+    environment::Environment,
+    limits::{LimitExceeded, Limits},
+    observer::Observer,
+    printer::Printer,
+    trace::{Operation, Step},
+    utils::{factorial, lgamma},
+    value::{Function, Value},
+    visit::preorder,
+};
+
+/// Maximum depth of nested function calls, a program going deeper
+/// than this is considered to be infinitely recursive.
+/// Calls nest on the stack, so `Limits::max_call_depth` can only lower it.
+pub const MAX_CALL_DEPTH: usize = 256;
+
+/// Name of the variable holding value of the last evaluated statement.
+pub const ANS: &str = "ans";
+
+/// How the interpreter treats the edge cases of floating point arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatPolicy {
+    // IEEE-754 semantics, `1/0` is `inf` and `0/0` is `NaN`.
+    #[default]
+    Ieee,
+    // division by zero, results which aren't a number, overflow to infinity
+    // and arguments outside the domain of a function are errors.
+    Strict,
+}
+
+/// Why an evaluation failed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EvalError {
+    // the evaluation ran into one of its `Limits`.
+    Limit(LimitExceeded),
+    // any other problem, like an unknown name or a wrong type.
+    Message(String),
+}
+
+impl core::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EvalError::Limit(limit) => write!(f, "{limit}"),
+            EvalError::Message(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<EvalError> for String {
+    fn from(error: EvalError) -> Self {
+        error.to_string()
+    }
+}
 
 /// Tree walk interpreter.
-pub struct Interpreter;
+pub struct Interpreter {
+    // functions and variables visible to the program.
+    environment: Environment,
+    // current depth of nested function calls.
+    depth: usize,
+    // depth of the expression the running function was called from.
+    nesting: usize,
+    // maximum depth of nested expressions, counting the expressions
+    // of every active function call, unlimited by default.
+    max_depth: usize,
+    // steps recorded so far, if tracing.
+    trace: Option<Vec<Step>>,
+    // the tree being evaluated and how many of its nodes were walked, if tracing.
+    traced: Option<(Rc<AST>, usize)>,
+    // hooks called while evaluating, if any.
+    observer: Option<Rc<RefCell<dyn Observer>>>,
+    limits: Limits,
+    // nodes evaluated and when the evaluation started, counted against the limits.
+    operations: u64,
+    started: Instant,
+    // the limit which stopped the running evaluation, till it fails with it.
+    exceeded: Option<LimitExceeded>,
+    float_policy: FloatPolicy,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Interpreter {
+    /// Constructor for interpreter.
+    ///
+    /// # Returns
+    /// * Interpreter - new interpreter with an empty environment.
+    pub fn new() -> Self {
+        Self {
+            environment: Environment::new(),
+            depth: 0,
+            nesting: 0,
+            max_depth: usize::MAX,
+            trace: None,
+            traced: None,
+            observer: None,
+            limits: Limits::default(),
+            operations: 0,
+            started: Instant::now(),
+            exceeded: None,
+            float_policy: FloatPolicy::default(),
+        }
+    }
+
+    /// Sets the maximum depth of nested expressions the interpreter evaluates.
+    /// # Arguments
+    /// * max_depth - the new limit.
+    /// # Returns
+    /// * Interpreter - the same interpreter with the new limit.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Records every step of evaluation, the sub-expression reduced, its operation,
+    /// the values of its operands and the result, in the order they happen.
+    /// Steps share a copy of the evaluated expression, made once per evaluation.
+    /// # Returns
+    /// * Interpreter - the same interpreter, recording steps.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    /// Sets how the interpreter treats division by zero, results which aren't
+    /// a number, overflow to infinity and arguments outside the domain of a function.
+    /// In strict mode they are errors naming the sub-expression which caused them.
+    /// Only what an operation produces is checked, infinite literals like `1e999` are
+    /// accepted and so are infinite results of infinite operands, `1e999 + 1`, but a
+    /// result which isn't a number is an error unless an operand already wasn't one,
+    /// so `1e999 - 1e999` fails.
+    /// # Arguments
+    /// * float_policy - the policy.
+    /// # Returns
+    /// * Interpreter - the same interpreter with the new policy.
+    /// # Examples
+    /// ```
+    /// use expr_solver::{interpreter::{FloatPolicy, Interpreter}, parse};
+    ///
+    /// let mut interpreter = Interpreter::new();
+    /// assert_eq!(interpreter.run(&parse("1 / 0").unwrap()).unwrap().as_number(), Ok(f64::INFINITY));
+    ///
+    /// let mut interpreter = Interpreter::new().with_float_policy(FloatPolicy::Strict);
+    /// assert_eq!(
+    ///     interpreter.run(&parse("2 + 1 / (3 - 3)").unwrap()).unwrap_err().to_string(),
+    ///     "Division by zero in '1 / (3 - 3)'."
+    /// );
+    /// ```
+    pub fn with_float_policy(mut self, float_policy: FloatPolicy) -> Self {
+        self.float_policy = float_policy;
+        self
+    }
+
+    /// Sets limits on the resources each evaluation can use, see `Limits`.
+    /// An evaluation running into one fails with `EvalError::Limit`, telling which.
+    /// # Arguments
+    /// * limits - the limits.
+    /// # Returns
+    /// * Interpreter - the same interpreter with the new limits.
+    /// # Examples
+    /// ```
+    /// use expr_solver::{
+    ///     interpreter::{EvalError, Interpreter},
+    ///     limits::{LimitExceeded, Limits},
+    ///     parse,
+    /// };
+    ///
+    /// let mut interpreter = Interpreter::new().with_limits(Limits {
+    ///     max_magnitude: Some(1e6),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// assert!(interpreter.run(&parse("9!").unwrap()).is_ok());
+    /// let error = interpreter.run(&parse("100!").unwrap()).unwrap_err();
+    /// assert_eq!(error, EvalError::Limit(LimitExceeded::Magnitude(1e6)));
+    /// assert_eq!(error.to_string(), "Value exceeded the maximum magnitude of 1000000.");
+    /// ```
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Calls the hooks of an observer while evaluating, see `Observer`.
+    /// The interpreter shares the observer, so the caller can keep a handle
+    /// to read what it collected.
+    /// # Arguments
+    /// * observer - the observer.
+    /// # Returns
+    /// * Interpreter - the same interpreter, calling the observer.
+    pub fn with_observer(mut self, observer: Rc<RefCell<dyn Observer>>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Replaces the observer, so an evaluation can be observed by someone else.
+    /// # Arguments
+    /// * observer - the new observer, None to stop observing.
+    /// # Returns
+    /// * `Option<Rc<RefCell<dyn Observer>>>` - the observer it had.
+    pub(crate) fn replace_observer(
+        &mut self,
+        observer: Option<Rc<RefCell<dyn Observer>>>,
+    ) -> Option<Rc<RefCell<dyn Observer>>> {
+        std::mem::replace(&mut self.observer, observer)
+    }
+
+    /// Takes the steps recorded so far, the next ones are recorded from scratch.
+    /// # Returns
+    /// * `Vec<Step>` - the steps, empty if the interpreter isn't tracing.
+    /// # Examples
+    /// ```
+    /// use expr_solver::{interpreter::Interpreter, parse};
+    ///
+    /// let mut interpreter = Interpreter::new().with_trace();
+    /// interpreter.run(&parse("f(x) = x * 2; f(3) + 1").unwrap()).unwrap();
+    ///
+    /// let steps: Vec<String> = interpreter.take_trace().iter().map(|s| s.to_string()).collect();
+    /// assert_eq!(steps, ["x * 2 = 6", "f(3) = 6", "f(3) + 1 = 7"]);
+    /// assert!(interpreter.take_trace().is_empty());
+    /// ```
+    pub fn take_trace(&mut self) -> Vec<Step> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
     /// Public function to starting walking a AST.
     /// # Arguments
     /// * ast : Reference to the AST to walk.
@@ -11,94 +242,731 @@ impl Interpreter {
     /// Result enum with the value of expression if interpretion was correct,
     /// otherwise string error.
     pub fn walk_ast(ast: &AST) -> Result<f64, String> {
-        match ast {
-            // if the entire ast is just one token.
-            AST::Node(number) => Interpreter::walk_node(number),
-            // walk the rest ast.
-            AST::Con(operator, sub_tokens) => Interpreter::solve_expr(operator, sub_tokens),
+        Interpreter::new().evaluate(ast)?.as_number()
+    }
+
+    /// Runs a program, defining its functions and evaluating its expressions in order.
+    /// # Arguments
+    /// * statements - the statements of the program.
+    /// # Returns
+    /// Result with value of the last statement, which must not be a function definition,
+    /// otherwise the error.
+    pub fn run(&mut self, statements: &[Statement]) -> Result<Value, EvalError> {
+        let mut result = None;
+
+        for statement in statements {
+            let value = self.execute(statement)?;
+            result = match statement {
+                Statement::Function(..) => None,
+                _ => Some(value),
+            };
         }
+
+        result.ok_or_else(|| {
+            EvalError::Message("Expected an expression after the definitions.".to_string())
+        })
+    }
+
+    /// Runs a script, keeping every definition and assignment in this interpreter
+    /// so following calls can use them.
+    /// # Arguments
+    /// * statements - the statements of the script.
+    /// # Returns
+    /// Result with value of every statement in order, otherwise error
+    /// of the first statement which failed.
+    pub fn run_script(&mut self, statements: &[Statement]) -> Result<Vec<Value>, EvalError> {
+        statements
+            .iter()
+            .map(|statement| self.execute(statement))
+            .collect()
     }
 
-    /// returns the inner valuue of a node, basically a Number Token.
+    /// Executes a single statement.
+    /// Expressions and assignments also store their value in the `ans` variable.
     /// # Arguments
-    /// * token - Reference to the token.
+    /// * statement - the statement to execute.
     /// # Returns
-    /// Result with value of the number inside the token, otherwise error string.
-    fn walk_node(token: &Token) -> Result<f64, String> {
-        match token {
-            Token::Number(f) => Ok(*f),
-            _ => Err("Unrecognised node token.".to_string()),
+    /// Result with value of the statement, a function definition evaluates
+    /// to the defined function, otherwise the error.
+    pub fn execute(&mut self, statement: &Statement) -> Result<Value, EvalError> {
+        match statement {
+            Statement::Function(name, parameters, body) => {
+                let function = Value::Function(Rc::new(Function {
+                    parameters: parameters.clone(),
+                    body: Rc::clone(body),
+                    captured: HashMap::new(),
+                }));
+                self.environment.define_variable(name, function.clone());
+                Ok(function)
+            }
+            Statement::Assignment(name, ast) => {
+                let value = self.evaluate(ast)?;
+                self.environment.define_variable(name, value.clone());
+                self.environment.define_variable(ANS, value.clone());
+                Ok(value)
+            }
+            Statement::Expression(ast) => {
+                let value = self.evaluate(ast)?;
+                self.environment.define_variable(ANS, value.clone());
+                Ok(value)
+            }
         }
     }
 
-    /// This is nothing but a wrapper which calls functions required
-    /// by recognising the type of expression from the length of operands.
+    /// Evaluates an expression in the current environment.
+    /// Work left to do is kept in a stack on the heap instead of recursing
+    /// into every sub expression, so the depth of the expression is limited
+    /// only by memory, or by `with_max_depth` if it is set.
     /// # Arguments
-    /// * token - Reference to the token.
-    /// * sub_tokens - Reference to vector of ast inside the current node.
+    /// * ast : Reference to the AST to evaluate.
     /// # Returns
-    /// The Result returned by the respective called function.
-    fn solve_expr(operator: &Token, sub_tokens: &[AST]) -> Result<f64, String> {
-        match sub_tokens.len() {
-            // if there are two operands, the expression is binary.
-            2 => Interpreter::solve_binary(operator, sub_tokens),
-            // if there is only one operand, the expression is unary.
-            1 => Interpreter::solve_unary(operator, sub_tokens),
-            // everything else is unreal according to this interpreter.
-            _ => Err("Unrecognised number of operands.".to_string()),
+    /// Result with value of the expression, otherwise the error.
+    pub fn evaluate(&mut self, ast: &AST) -> Result<Value, EvalError> {
+        // function bodies are evaluated as part of their caller's evaluation.
+        if self.depth == 0 {
+            self.operations = 0;
+            self.started = Instant::now();
+            self.exceeded = None;
         }
+
+        let tree = self.trace.is_some().then(|| Rc::new(ast.clone()));
+        self.evaluate_tree(ast, tree)
+            .map_err(|message| match self.exceeded.take() {
+                Some(limit) => EvalError::Limit(limit),
+                None => EvalError::Message(message),
+            })
     }
 
-    /// Solves a binary expression.
+    /// Evaluates an expression in the current environment.
+    /// # Arguments
+    /// * ast : Reference to the AST to evaluate.
+    /// * tree - the same tree, shared by the steps recorded while evaluating it, if tracing.
+    /// # Returns
+    /// Result with value of the expression, otherwise error string.
+    fn evaluate_tree(&mut self, ast: &AST, tree: Option<Rc<AST>>) -> Result<Value, String> {
+        let caller = std::mem::replace(&mut self.traced, tree.map(|tree| (tree, 0)));
+        let mut tasks = vec![Task::Evaluate(ast, self.nesting)];
+        let mut values = vec![];
+
+        let result = self.run_tasks(&mut tasks, &mut values);
+        self.traced = caller;
+
+        // after an error, bindings of unfinished let expressions are still in scope.
+        for task in tasks {
+            if let Task::Unbind = task {
+                self.environment.pop_scope();
+            }
+        }
+
+        result?;
+        pop(&mut values)
+    }
+
+    /// Runs tasks till there are none left, every task pops the values
+    /// it needs and pushes the value it produces.
+    /// # Arguments
+    /// * tasks - stack of tasks to run.
+    /// * values - stack of evaluated values.
+    /// # Returns
+    /// Result with nothing, otherwise error string of the first failed task.
+    fn run_tasks<'t>(
+        &mut self,
+        tasks: &mut Vec<Task<'t>>,
+        values: &mut Vec<Value>,
+    ) -> Result<(), String> {
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Evaluate(ast, depth) => self.walk(ast, depth, tasks, values)?,
+                Task::Binary(operator, ast, node) => {
+                    let right = pop(values)?.as_number()?;
+                    let left = pop(values)?.as_number()?;
+                    let result = Self::solve_binary(operator, left, right);
+                    let operation = Operation::Binary(operator);
+                    self.check_float(ast, &operation, &[left, right], result)?;
+                    let value = Value::Number(result);
+                    self.check_size(&value)?;
+                    let operands = || vec![left.into(), right.into()];
+                    self.record(ast, node, operation, operands, &value);
+                    values.push(value);
+                }
+                Task::Unary(operator, ast, node) => {
+                    let right = pop(values)?.as_number()?;
+                    let result = Self::solve_unary(operator, right);
+                    let operation = Operation::Unary(operator);
+                    self.check_float(ast, &operation, &[right], result)?;
+                    let value = Value::Number(result);
+                    self.check_size(&value)?;
+                    self.record(ast, node, operation, || vec![right.into()], &value);
+                    values.push(value);
+                }
+                Task::Postfix(operator, ast, node) => {
+                    let left = pop(values)?.as_number()?;
+                    // gamma has its poles there, whatever the policy.
+                    if operator == PostfixOperator::Factorial && left < 0.0 && left.fract() == 0.0 {
+                        return Err(format!(
+                            "Factorial of a negative integer in '{}'.",
+                            Printer::new().print(ast)
+                        ));
+                    }
+                    let result = Self::solve_postfix(operator, left);
+                    let operation = Operation::Postfix(operator);
+                    self.check_float(ast, &operation, &[left], result)?;
+                    let value = Value::Number(result);
+                    self.check_size(&value)?;
+                    self.record(ast, node, operation, || vec![left.into()], &value);
+                    values.push(value);
+                }
+                Task::Call(name, count, depth, ast, node) => {
+                    let arguments = values.split_off(values.len() - count);
+                    let operands = self.observed().then(|| arguments.clone());
+                    // calls of numeric builtins are checked like operators, defined
+                    // functions only by the operators and calls in their body.
+                    let builtin = self.environment.variable(name).is_none();
+                    let numbers: Option<Vec<f64>> = match self.float_policy {
+                        FloatPolicy::Strict if builtin => {
+                            arguments.iter().map(|v| v.as_number().ok()).collect()
+                        }
+                        _ => None,
+                    };
+
+                    // the body of the called function is nested inside the call.
+                    let caller = std::mem::replace(&mut self.nesting, depth + 1);
+                    let value = self.solve_call(name, arguments);
+                    self.nesting = caller;
+
+                    let value = value?;
+                    let operation = Operation::Call(name.to_string());
+                    if let (Some(numbers), Value::Number(result)) = (&numbers, &value) {
+                        self.check_float(ast, &operation, numbers, *result)?;
+                    }
+                    self.check_size(&value)?;
+                    self.record(
+                        ast,
+                        node,
+                        operation,
+                        || operands.unwrap_or_default(),
+                        &value,
+                    );
+                    values.push(value);
+                }
+                Task::List(count) => {
+                    let items = values.split_off(values.len() - count);
+                    let value = Value::List(Rc::new(items));
+                    self.check_size(&value)?;
+                    values.push(value);
+                }
+                Task::Bind(name, body, depth) => {
+                    let value = pop(values)?;
+                    self.environment
+                        .push_scope(HashMap::from([(name.to_string(), value)]));
+                    tasks.push(Task::Unbind);
+                    tasks.push(Task::Evaluate(body, depth));
+                }
+                Task::Unbind => self.environment.pop_scope(),
+                Task::Leave(ast) => {
+                    if let (Some(observer), Some(value)) = (&self.observer, values.last()) {
+                        observer.borrow_mut().leave(ast, value);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a single node of the expression, leaf nodes push their value
+    /// right away, other nodes push tasks evaluating their children first.
+    /// # Arguments
+    /// * ast : Reference to the AST to evaluate.
+    /// * depth - how deep the node is nested.
+    /// * tasks - stack of tasks to run.
+    /// * values - stack of evaluated values.
+    /// # Returns
+    /// Result with nothing, otherwise error string.
+    fn walk<'t>(
+        &mut self,
+        ast: &'t AST,
+        depth: usize,
+        tasks: &mut Vec<Task<'t>>,
+        values: &mut Vec<Value>,
+    ) -> Result<(), String> {
+        if depth >= self.max_depth {
+            return Err(self.exceed(LimitExceeded::Nesting(self.max_depth)));
+        }
+        self.count_operation()?;
+
+        // position of the node in the traced tree, in preorder.
+        let node = match &mut self.traced {
+            Some((_, walked)) => {
+                *walked += 1;
+                *walked - 1
+            }
+            None => 0,
+        };
+
+        // the node is left once every task it pushes is done.
+        if let Some(observer) = &self.observer {
+            observer.borrow_mut().enter(ast);
+            tasks.push(Task::Leave(ast));
+        }
+
+        // children are pushed in reverse, so they are evaluated from left to right.
+        let children = match ast {
+            // if the entire ast is just a number.
+            AST::Literal(n) => {
+                values.push(Value::Number(*n));
+                return Ok(());
+            }
+            AST::Var(name) => {
+                let value = self
+                    .environment
+                    .variable(name)
+                    .ok_or_else(|| format!("Unknown variable '{name}'."))?;
+                values.push(value);
+                return Ok(());
+            }
+            // lambdas capture every local variable visible right now.
+            AST::Lambda(parameters, body) => {
+                // the body isn't walked, but its nodes still take up positions.
+                if let Some((_, walked)) = &mut self.traced {
+                    *walked += preorder(body).count();
+                }
+                values.push(Value::Function(Rc::new(Function {
+                    parameters: parameters.clone(),
+                    body: Rc::clone(body),
+                    captured: self.environment.capture(),
+                })));
+                return Ok(());
+            }
+            // walk the rest ast.
+            AST::Unary { op, operand } => {
+                tasks.push(Task::Unary(*op, ast, node));
+                tasks.push(Task::Evaluate(operand, depth + 1));
+                return Ok(());
+            }
+            AST::Postfix { op, operand } => {
+                tasks.push(Task::Postfix(*op, ast, node));
+                tasks.push(Task::Evaluate(operand, depth + 1));
+                return Ok(());
+            }
+            AST::Binary { op, lhs, rhs } => {
+                tasks.push(Task::Binary(*op, ast, node));
+                tasks.push(Task::Evaluate(rhs, depth + 1));
+                tasks.push(Task::Evaluate(lhs, depth + 1));
+                return Ok(());
+            }
+            AST::Call(name, arguments) => {
+                tasks.push(Task::Call(name, arguments.len(), depth, ast, node));
+                arguments
+            }
+            AST::List(items) => {
+                tasks.push(Task::List(items.len()));
+                items
+            }
+            // the body is evaluated once the value is bound.
+            AST::Let(name, value, body) => {
+                tasks.push(Task::Bind(name, body, depth + 1));
+                tasks.push(Task::Evaluate(value, depth + 1));
+                return Ok(());
+            }
+        };
+
+        tasks.extend(
+            children
+                .iter()
+                .rev()
+                .map(|child| Task::Evaluate(child, depth + 1)),
+        );
+        Ok(())
+    }
+
+    /// Counts a node evaluated, checking the operation budget, the time budget
+    /// and the cancellation flag.
+    /// # Returns
+    /// Result with nothing, otherwise error string of the exceeded limit.
+    fn count_operation(&mut self) -> Result<(), String> {
+        self.operations += 1;
+
+        if let Some(max) = self.limits.max_operations {
+            if self.operations > max {
+                return Err(self.exceed(LimitExceeded::Operations(max)));
+            }
+        }
+        if let Some(max) = self.limits.max_time {
+            if self.started.elapsed() > max {
+                return Err(self.exceed(LimitExceeded::Time(max)));
+            }
+        }
+        if let Some(cancel) = &self.limits.cancel {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(self.exceed(LimitExceeded::Cancelled));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a value computed along the way against the limits on its size.
+    /// # Arguments
+    /// * value - the value.
+    /// # Returns
+    /// Result with nothing, otherwise error string of the exceeded limit.
+    fn check_size(&mut self, value: &Value) -> Result<(), String> {
+        match (
+            value,
+            self.limits.max_magnitude,
+            self.limits.max_list_length,
+        ) {
+            (Value::Number(n), Some(max), _) if n.abs() > max => {
+                Err(self.exceed(LimitExceeded::Magnitude(max)))
+            }
+            (Value::List(items), _, Some(max)) if items.len() > max => {
+                Err(self.exceed(LimitExceeded::ListLength(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the result of an operator or a numeric function in strict mode,
+    /// the result fails if it is infinite while every operand was finite,
+    /// or isn't a number while every operand was one.
     /// # Arguments
-    /// * token - Reference to the token.
-    /// * sub_tokens - Reference to vector of ast inside the current node.
+    /// * ast - the node, named in errors.
+    /// * operation - the applied operator or the called function.
+    /// * operands - values of the operands or arguments.
+    /// * result - the value it gave.
     /// # Returns
-    /// Result with value after solving the binary expresion, otherwise error string.
-    fn solve_binary(operator: &Token, sub_tokens: &[AST]) -> Result<f64, String> {
-        // the left operand.
-        let left = match Interpreter::walk_ast(&sub_tokens[0]) {
-            Ok(left) => left,
-            Err(e) => return Err(e),
+    /// Result with nothing, otherwise error string naming the problem.
+    fn check_float(
+        &self,
+        ast: &AST,
+        operation: &Operation,
+        operands: &[f64],
+        result: f64,
+    ) -> Result<(), String> {
+        let produced = if result.is_nan() {
+            !operands.iter().any(|n| n.is_nan())
+        } else {
+            result.is_infinite() && operands.iter().all(|n| n.is_finite())
         };
+        if self.float_policy == FloatPolicy::Ieee || !produced {
+            return Ok(());
+        }
 
-        // the right operand..
-        let right = match Interpreter::walk_ast(&sub_tokens[1]) {
-            Ok(right) => right,
-            Err(e) => return Err(e),
+        let problem = match operation {
+            Operation::Binary(BinaryOperator::Divide) if operands[1] == 0.0 => "Division by zero",
+            Operation::Unary(UnaryOperator::Root) => "Square root of a negative number",
+            Operation::Call(name) => match name.as_str() {
+                "sqrt" => "Square root of a negative number",
+                "ln" | "log" if result.is_nan() => "Logarithm of a negative number",
+                "ln" | "log" => "Logarithm of zero",
+                "lgamma" => "Pole of gamma",
+                _ if result.is_nan() => "Argument outside the domain of the function",
+                _ => "Overflow to infinity",
+            },
+            _ if result.is_nan() => "Result is not a number",
+            _ => "Overflow to infinity",
         };
 
+        Err(format!("{problem} in '{}'.", Printer::new().print(ast)))
+    }
+
+    /// Remembers the limit which stopped the evaluation.
+    /// # Arguments
+    /// * limit - the exceeded limit.
+    /// # Returns
+    /// * String - the error message.
+    fn exceed(&mut self, limit: LimitExceeded) -> String {
+        let message = limit.to_string();
+        self.exceeded = Some(limit);
+        message
+    }
+
+    /// Checks whether steps of evaluation are traced or observed.
+    fn observed(&self) -> bool {
+        self.trace.is_some() || self.observer.is_some()
+    }
+
+    /// Records a step of evaluation, if tracing, and tells the observer about it.
+    /// # Arguments
+    /// * ast - the reduced sub-expression.
+    /// * node - position of the sub-expression in the traced tree.
+    /// * operation - what the step did.
+    /// * operands - gives the values of the operands, only called if observed.
+    /// * result - value of the sub-expression.
+    fn record(
+        &mut self,
+        ast: &AST,
+        node: usize,
+        operation: Operation,
+        operands: impl FnOnce() -> Vec<Value>,
+        result: &Value,
+    ) {
+        if !self.observed() {
+            return;
+        }
+
+        let operands = operands();
+        if let Some(observer) = &self.observer {
+            observer
+                .borrow_mut()
+                .apply(ast, &operation, &operands, result);
+        }
+        if let (Some(trace), Some((tree, _))) = (&mut self.trace, &self.traced) {
+            trace.push(Step {
+                tree: Rc::clone(tree),
+                node,
+                operation,
+                operands,
+                result: result.clone(),
+                depth: self.depth,
+            });
+        }
+    }
+
+    /// Solves a binary expression.
+    /// # Arguments
+    /// * operator - the binary operator.
+    /// * left - value of the left operand.
+    /// * right - value of the right operand.
+    /// # Returns
+    /// Value after solving the binary expresion.
+    fn solve_binary(operator: BinaryOperator, left: f64, right: f64) -> f64 {
         log::trace!("Solving binary left={left}  operator={operator} right={right}");
         // checking type of operator, and solving accordingly.
         match operator {
-            Token::Plus => Ok(left + right),
-            Token::Minus => Ok(left - right),
-            Token::Star => Ok(left * right),
-            Token::Slash => Ok(left / right),
-            _ => Err("Unrecognised binary operator.".to_string()),
-            //
+            BinaryOperator::Add => left + right,
+            BinaryOperator::Subtract => left - right,
+            BinaryOperator::Multiply => left * right,
+            BinaryOperator::Divide => left / right,
+            BinaryOperator::Power => left.powf(right),
+            BinaryOperator::Less => truth(left < right),
+            BinaryOperator::LessEqual => truth(left <= right),
+            BinaryOperator::Greater => truth(left > right),
+            BinaryOperator::GreaterEqual => truth(left >= right),
+            BinaryOperator::Equal => truth(left == right),
+            BinaryOperator::NotEqual => truth(left != right),
         }
     }
 
-    /// Solves a binary expression.
+    /// Solves a unary expression.
     /// # Arguments
-    /// * token - Reference to the token.
-    /// * sub_tokens - Reference to vector of ast inside the current node.
+    /// * operator - the unary operator.
+    /// * right - value of the only operand.
     /// # Returns
-    /// Result with value after solving the binary expresion, otherwise error string.
-    fn solve_unary(operator: &Token, sub_tokens: &[AST]) -> Result<f64, String> {
-        // the only right operand.
-        let right = match Interpreter::walk_ast(&sub_tokens[0]) {
-            Ok(right) => right,
-            Err(e) => return Err(e),
-        };
-
-        log::trace!("Solving binary operator={operator} right={right}");
+    /// Value after solving the unary expresion.
+    fn solve_unary(operator: UnaryOperator, right: f64) -> f64 {
+        log::trace!("Solving unary operator={operator} right={right}");
         // checking type of operator and solving accordingly.
         match operator {
-            Token::Minus => Ok(-right),
-            Token::Bang => Ok(factorial(right)),
-            _ => Err("Unrecognised binary operator.".to_string()),
+            UnaryOperator::Plus => right,
+            UnaryOperator::Negate => -right,
+            UnaryOperator::Root => right.sqrt(),
+        }
+    }
+
+    /// Solves a postfix expression.
+    /// # Arguments
+    /// * operator - the postfix operator.
+    /// * left - value of the only operand.
+    /// # Returns
+    /// Value after solving the postfix expresion.
+    fn solve_postfix(operator: PostfixOperator, left: f64) -> f64 {
+        log::trace!("Solving postfix left={left} operator={operator}");
+        match operator {
+            PostfixOperator::Factorial => factorial(left),
+        }
+    }
+
+    /// Solves a function call, functions visible by name take precedence over builtins.
+    /// # Arguments
+    /// * name - name of the called function.
+    /// * values - values of the arguments.
+    /// # Returns
+    /// Result with value returned by the function, otherwise error string.
+    fn solve_call(&mut self, name: &str, values: Vec<Value>) -> Result<Value, String> {
+        log::trace!("Solving call name={name} arguments={values:?}");
+        match self.environment.variable(name) {
+            Some(Value::Function(ref function)) => self.call_function(name, function, values),
+            Some(_) => Err(format!("'{name}' is not a function.")),
+            None => self.call_builtin(name, values),
+        }
+    }
+
+    /// Calls a function value with already evaluated arguments.
+    /// # Arguments
+    /// * name - name of the function, used in errors.
+    /// * function - the function to call.
+    /// * values - values of the arguments.
+    /// # Returns
+    /// Result with value returned by the function, otherwise error string.
+    fn call_function(
+        &mut self,
+        name: &str,
+        function: &Function,
+        values: Vec<Value>,
+    ) -> Result<Value, String> {
+        if function.parameters.len() != values.len() {
+            return Err(format!(
+                "Function '{name}' expects {} argument(s), recieved {}.",
+                function.parameters.len(),
+                values.len()
+            ));
+        }
+
+        // without conditionals every recursive call is infinite,
+        // so running too deep means we found recursion.
+        let max_call_depth = self.limits.max_call_depth.min(MAX_CALL_DEPTH);
+        if self.depth >= max_call_depth {
+            return Err(self.exceed(LimitExceeded::CallDepth(max_call_depth)));
+        }
+
+        // parameters shadow captured variables.
+        let mut scope = function.captured.clone();
+        scope.extend(function.parameters.iter().cloned().zip(values));
+
+        self.depth += 1;
+        let caller = self.environment.enter_call(scope);
+        let tree = self.trace.is_some().then(|| Rc::clone(&function.body));
+        let result = self.evaluate_tree(&function.body, tree);
+        self.environment.exit_call(caller);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Calls a builtin function, either a higher order function over lists
+    /// or a plain numeric function.
+    /// # Arguments
+    /// * name - name of the function.
+    /// * values - values of the arguments.
+    /// # Returns
+    /// Result with value returned by the function, otherwise error string.
+    fn call_builtin(&mut self, name: &str, values: Vec<Value>) -> Result<Value, String> {
+        match (name, values.as_slice()) {
+            // map(f, list), applies f to each item.
+            ("map", [Value::Function(function), Value::List(items)]) => {
+                let mut mapped = vec![];
+                for item in items.iter() {
+                    mapped.push(self.call_function(name, function, vec![item.clone()])?);
+                }
+                Ok(Value::List(Rc::new(mapped)))
+            }
+
+            // filter(f, list), keeps items for which f is not zero.
+            ("filter", [Value::Function(function), Value::List(items)]) => {
+                let mut kept = vec![];
+                for item in items.iter() {
+                    let keep = self.call_function(name, function, vec![item.clone()])?;
+                    if keep.as_number()? != 0.0 {
+                        kept.push(item.clone());
+                    }
+                }
+                Ok(Value::List(Rc::new(kept)))
+            }
+
+            // reduce(f, list, initial), folds the items from left using f,
+            // the first item is the initial value if none is given.
+            ("reduce", [Value::Function(function), Value::List(items), rest @ ..])
+                if rest.len() <= 1 =>
+            {
+                let mut items = items.iter().cloned();
+                let mut accumulator = match rest.first().cloned().or_else(|| items.next()) {
+                    Some(accumulator) => accumulator,
+                    None => return Err("Cannot reduce an empty list without initial value.".to_string()),
+                };
+
+                for item in items {
+                    accumulator = self.call_function(name, function, vec![accumulator, item])?;
+                }
+                Ok(accumulator)
+            }
+
+            ("map" | "filter", _) => Err(format!(
+                "Function '{name}' expects a function and a list as arguments."
+            )),
+
+            ("reduce", _) => Err(
+                "Function 'reduce' expects a function, a list and an optional initial value as arguments."
+                    .to_string(),
+            ),
+
+            _ => {
+                let mut numbers = vec![];
+                for value in &values {
+                    numbers.push(value.as_number()?);
+                }
+                numeric_builtin(name, &numbers).map(Value::Number)
+            }
         }
     }
 }
+
+/// Work left to do while evaluating an expression.
+enum Task<'t> {
+    // evaluate an expression nested at the given depth, pushing its value.
+    Evaluate(&'t AST, usize),
+    // apply an operator to the values of its operands, the node and its
+    // position in the tree are recorded in traces.
+    Binary(BinaryOperator, &'t AST, usize),
+    Unary(UnaryOperator, &'t AST, usize),
+    Postfix(PostfixOperator, &'t AST, usize),
+    // call a function with the values of its arguments, from the given depth.
+    Call(&'t str, usize, usize, &'t AST, usize),
+    // collect the values of the items into a list.
+    List(usize),
+    // bind the value to the name, then evaluate the body at the given depth.
+    Bind(&'t str, &'t AST, usize),
+    // remove the binding once the body is evaluated.
+    Unbind,
+    // tell the observer the node is evaluated.
+    Leave(&'t AST),
+}
+
+/// Pops the value of the last evaluated expression.
+fn pop(values: &mut Vec<Value>) -> Result<Value, String> {
+    values
+        .pop()
+        .ok_or_else(|| "Expression evaluated to nothing.".to_string())
+}
+
+/// Converts a boolean into a number, 1 for true and 0 for false.
+fn truth(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Calls a builtin numeric function.
+/// # Arguments
+/// * name - name of the function.
+/// * arguments - values of the arguments.
+/// # Returns
+/// Result with value returned by the function, otherwise error string.
+fn numeric_builtin(name: &str, arguments: &[f64]) -> Result<f64, String> {
+    let function: fn(f64) -> f64 = match name {
+        "sqrt" => f64::sqrt,
+        "abs" => f64::abs,
+        "exp" => f64::exp,
+        "lgamma" => lgamma,
+        "ln" => f64::ln,
+        "log" => f64::log10,
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "asin" => f64::asin,
+        "acos" => f64::acos,
+        "atan" => f64::atan,
+        _ => return Err(format!("Unknown function '{name}'.")),
+    };
+
+    match arguments {
+        [argument] => Ok(function(*argument)),
+        _ => Err(format!(
+            "Function '{name}' expects 1 argument(s), recieved {}.",
+            arguments.len()
+        )),
+    }
+}
@@ -0,0 +1,242 @@
+use crate::ast::{BinaryOperator, UnaryOperator, AST};
+use crate::printer::{scientific, typeset_parens, Operand};
+
+// omicron has no command, it looks like a latin o.
+const GREEK_LETTERS: [&str; 23] = [
+    "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa",
+    "lambda", "mu", "nu", "xi", "pi", "rho", "sigma", "tau", "upsilon", "phi", "chi", "psi",
+    "omega",
+];
+
+// what is left to render of an ast.
+enum Piece<'t> {
+    Ast(&'t AST),
+    Text(&'static str),
+}
+
+/// Renders an expression as LaTeX math, `\frac{a}{b}`, `x^{2}`, `n!`, `\sqrt{x}`
+/// or `\sin\left(x\right)`, grouping operands with `\left(` and `\right)` only
+/// where precedence needs it.
+/// Fractions, roots and exponents group their contents themselves, so they
+/// never need parentheses inside.
+/// # Arguments
+/// * ast - the expression.
+/// # Returns
+/// * String - the LaTeX, without the surrounding `$`.
+/// # Examples
+/// ```
+/// use expr_solver::{latex::to_latex, parse_expression};
+///
+/// let ast = parse_expression("(a + b) / 2 * sin(x)^2 - √n!").unwrap();
+/// assert_eq!(
+///     to_latex(&ast),
+///     r"\frac{a + b}{2} \cdot \sin\left(x\right)^{2} - \sqrt{n!}"
+/// );
+/// ```
+pub fn to_latex(ast: &AST) -> String {
+    let mut out = String::new();
+    let mut pending = vec![Piece::Ast(ast)];
+
+    while let Some(piece) = pending.pop() {
+        let ast = match piece {
+            Piece::Text(text) => {
+                out.push_str(text);
+                continue;
+            }
+            Piece::Ast(ast) => ast,
+        };
+
+        // pieces are pushed in reverse, so they are rendered in order.
+        match ast {
+            AST::Literal(n) => out.push_str(&number(*n)),
+            AST::Var(name) => out.push_str(&symbol(name)),
+            AST::Unary { op, operand } => match op {
+                UnaryOperator::Root => {
+                    out.push_str(r"\sqrt{");
+                    pending.push(Piece::Text("}"));
+                    pending.push(Piece::Ast(operand));
+                }
+                _ => {
+                    let ((), right_bp) = op.binding_power();
+                    out.push_str(op.symbol());
+                    push_operand(&mut pending, operand, Operand::Right(right_bp));
+                }
+            },
+            AST::Postfix { op, operand } => {
+                pending.push(Piece::Text(op.symbol()));
+                push_operand(&mut pending, operand, Operand::Base);
+            }
+            AST::Binary { op, lhs, rhs } => match op {
+                BinaryOperator::Divide => {
+                    out.push_str(r"\frac{");
+                    pending.push(Piece::Text("}"));
+                    pending.push(Piece::Ast(rhs));
+                    pending.push(Piece::Text("}{"));
+                    pending.push(Piece::Ast(lhs));
+                }
+                BinaryOperator::Power => {
+                    pending.push(Piece::Text("}"));
+                    pending.push(Piece::Ast(rhs));
+                    pending.push(Piece::Text("^{"));
+                    push_operand(&mut pending, lhs, Operand::Base);
+                }
+                _ => {
+                    let (left_bp, right_bp) = op.binding_power();
+                    push_operand(&mut pending, rhs, Operand::Right(right_bp));
+                    pending.push(Piece::Text(operator(*op)));
+                    push_operand(&mut pending, lhs, Operand::Left(left_bp));
+                }
+            },
+            AST::Call(name, arguments) => {
+                let (open, close) = match name.as_str() {
+                    "sqrt" if arguments.len() == 1 => (r"\sqrt{", "}"),
+                    "abs" if arguments.len() == 1 => (r"\left|", r"\right|"),
+                    _ => {
+                        out.push_str(&function(name));
+                        (r"\left(", r"\right)")
+                    }
+                };
+                push_items(&mut pending, open, arguments, close);
+            }
+            AST::List(items) => push_items(&mut pending, r"\left[", items, r"\right]"),
+            AST::Let(name, value, body) => {
+                out.push_str(r"\operatorname{let} ");
+                out.push_str(&symbol(name));
+                pending.push(Piece::Ast(body));
+                pending.push(Piece::Text(r" \operatorname{in} "));
+                pending.push(Piece::Ast(value));
+                pending.push(Piece::Text(" = "));
+            }
+            AST::Lambda(parameters, body) => {
+                let parameters: Vec<String> = parameters.iter().map(|p| symbol(p)).collect();
+                match parameters.as_slice() {
+                    [parameter] => out.push_str(parameter),
+                    _ => out.push_str(&format!(r"\left({}\right)", parameters.join(", "))),
+                }
+                pending.push(Piece::Ast(body));
+                pending.push(Piece::Text(r" \mapsto "));
+            }
+        }
+    }
+
+    out
+}
+
+/// Pushes an operand, wrapped in parentheses if needed.
+/// # Arguments
+/// * pending - pieces left to render.
+/// * operand - the operand.
+/// * position - where the operand sits.
+fn push_operand<'t>(pending: &mut Vec<Piece<'t>>, operand: &'t AST, position: Operand) {
+    if typeset_parens(operand, position) {
+        pending.push(Piece::Text(r"\right)"));
+        pending.push(Piece::Ast(operand));
+        pending.push(Piece::Text(r"\left("));
+    } else {
+        pending.push(Piece::Ast(operand));
+    }
+}
+
+/// Pushes items between delimiters, separated by commas.
+/// # Arguments
+/// * pending - pieces left to render.
+/// * open - the opening delimiter.
+/// * items - the items.
+/// * close - the closing delimiter.
+fn push_items<'t>(
+    pending: &mut Vec<Piece<'t>>,
+    open: &'static str,
+    items: &'t [AST],
+    close: &'static str,
+) {
+    pending.push(Piece::Text(close));
+    for (i, item) in items.iter().enumerate().rev() {
+        pending.push(Piece::Ast(item));
+        if i > 0 {
+            pending.push(Piece::Text(", "));
+        }
+    }
+    pending.push(Piece::Text(open));
+}
+
+/// Returns the LaTeX of an infix operator, with spaces around it.
+fn operator(op: BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => " + ",
+        BinaryOperator::Subtract => " - ",
+        BinaryOperator::Multiply => r" \cdot ",
+        BinaryOperator::Divide => " / ",
+        BinaryOperator::Power => "^",
+        BinaryOperator::Less => " < ",
+        BinaryOperator::LessEqual => r" \leq ",
+        BinaryOperator::Greater => " > ",
+        BinaryOperator::GreaterEqual => r" \geq ",
+        BinaryOperator::Equal => " = ",
+        BinaryOperator::NotEqual => r" \neq ",
+    }
+}
+
+/// Renders the name of a variable, greek letters become their symbols
+/// and longer names are set upright.
+fn symbol(name: &str) -> String {
+    if GREEK_LETTERS.contains(&name) {
+        return format!(r"\{name}");
+    }
+
+    if name.chars().count() == 1 {
+        escape(name)
+    } else {
+        format!(r"\mathrm{{{}}}", escape(name))
+    }
+}
+
+/// Renders the name of a function, the ones LaTeX knows use their own command.
+fn function(name: &str) -> String {
+    match name {
+        "sin" | "cos" | "tan" | "exp" | "ln" | "log" => format!(r"\{name}"),
+        "asin" | "acos" | "atan" => format!(r"\arc{}", &name[1..]),
+        _ if name.chars().count() == 1 => escape(name),
+        _ => format!(r"\operatorname{{{}}}", escape(name)),
+    }
+}
+
+/// Escapes the characters LaTeX gives a meaning to, names read from
+/// s-expressions, binary blobs or json can contain any of them.
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\backslash{}"),
+            '^' => escaped.push_str(r"\hat{}"),
+            '~' => escaped.push_str(r"\tilde{}"),
+            '{' | '}' | '#' | '$' | '%' | '&' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders a number, very large and very small ones in scientific notation.
+fn number(n: f64) -> String {
+    if n.is_nan() {
+        return r"\mathrm{NaN}".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { r"\infty" } else { r"-\infty" }.to_string();
+    }
+
+    if !scientific(n) {
+        return n.to_string();
+    }
+
+    let scientific = format!("{n:e}");
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    match mantissa {
+        "1" => format!("10^{{{exponent}}}"),
+        "-1" => format!("-10^{{{exponent}}}"),
+        _ => format!(r"{mantissa} \times 10^{{{exponent}}}"),
+    }
+}
@@ -1,24 +1,37 @@
-use crate::token::Token;
+use crate::token::{Span, SpannedToken, Token};
+
+/// Error found while scanning a token.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexError {
+    // description of the error.
+    pub message: String,
+    // where the invalid text is in the source string.
+    pub span: Span,
+}
+
+impl core::fmt::Display for LexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
 
 // top level lexer.
-pub struct Lexer {
+// scans tokens lazily from a borrowed source string, one at a time.
+// every position is a byte offset into the source string,
+// always on a char boundary.
+pub struct Lexer<'a> {
     // source string.
-    pub source_string: String,
-    // source string as vec of chars.
-    pub source_chars: Vec<char>,
-    // start of current token.
-    pub start: usize,
-    // current position of the character in consideration.
-    pub current: usize,
-    // Vec of tokens.
-    pub tokens: Vec<Token>,
-    // len of the input string.
-    pub len: usize,
-    // flag for errors.
-    pub has_errors: bool,
+    source: &'a str,
+    // byte offset of start of current token.
+    start: usize,
+    // byte offset of the character in consideration.
+    current: usize,
+    // numbers can be followed by names, an 'e' without exponent digits starts one.
+    juxtaposition: bool,
 }
 
-impl Lexer {
+impl<'a> Lexer<'a> {
     /// Constructor for lexer
     ///
     /// # Arguments
@@ -26,131 +39,322 @@ impl Lexer {
     ///
     /// # Returns
     /// * Lexer - new instance of lexer with prepopulated fields.
-    pub fn new(input: &String) -> Self {
+    pub fn new(input: &'a str) -> Self {
         log::trace!(" [expr-solver] creating new lexer instance : {}", &input);
         Self {
-            source_chars: input.chars().collect(),
-            source_string: input.clone(),
+            source: input,
             start: 0,
             current: 0,
-            len: input.len(),
-            tokens: vec![],
-            has_errors: false,
+            juxtaposition: false,
         }
     }
 
-    /// Top level public function to start tokenizing the input string.
-    pub fn scan(&mut self) {
-        log::trace!("[expr-solver] starting scanning for tokens.");
-        while !self.is_at_end() {
-            self.scan_token();
-            self.start = self.current;
-        }
-
-        // we add a EOF token at the end, comes handy when parsing.
-        self.add_token(Token::Eof);
-
-        log::trace!(
-            "[expr-solver] Scanned {} token. tokens={:?}",
-            self.tokens.len(),
-            &self.tokens
-        );
-
-        // we reverse the entire tokens vec, because we will be
-        // using pop function to retrieve one token at time, which
-        // takes tokens from the end.
-        self.tokens.reverse();
+    /// Scans numbers for implicit multiplication, an 'e' which isn't followed
+    /// by the digits of an exponent ends the number instead of being an error,
+    /// so `2e` is `2` times the constant `e` and `2exp(1)` is `2` times a call.
+    /// # Returns
+    /// * Lexer - the same lexer, scanning numbers followed by names.
+    pub fn with_juxtaposition(mut self) -> Self {
+        self.juxtaposition = true;
+        self
     }
 
     /// Internal function which parses one token at a time.
-    fn scan_token(&mut self) {
+    /// # Returns
+    /// The scanned token, or error string if the text is not a valid token,
+    /// None if the text was whitespace.
+    fn scan_token(&mut self) -> Option<Result<Token<'a>, String>> {
         let current_char = self.advance();
 
-        match current_char {
-            '+' => self.add_token(Token::Plus),
-            '-' => self.add_token(Token::Minus),
-            '*' => self.add_token(Token::Star),
-            '/' => self.add_token(Token::Slash),
-            '!' => self.add_token(Token::Bang),
-            '(' => self.add_token(Token::LeftParen),
-            ')' => self.add_token(Token::RightParen),
-            ' ' | '\t' | '\r' => {}
+        let token = match current_char {
+            '+' => Token::Plus,
+            // hyphen and the unicode minus sign.
+            '-' | '−' => Token::Minus,
+            '*' | '×' => Token::Star,
+            '/' | '÷' => Token::Slash,
+            '^' => Token::Caret,
+            '√' => Token::Root,
+            'π' => Token::Identifier("pi"),
+            '!' => {
+                if self.advance_if('=') {
+                    Token::BangEqual
+                } else {
+                    Token::Bang
+                }
+            }
+            '<' => {
+                if self.advance_if('=') {
+                    Token::LessEqual
+                } else {
+                    Token::Less
+                }
+            }
+            '>' => {
+                if self.advance_if('=') {
+                    Token::GreaterEqual
+                } else {
+                    Token::Greater
+                }
+            }
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '=' => {
+                if self.advance_if('>') {
+                    Token::FatArrow
+                } else if self.advance_if('=') {
+                    Token::EqualEqual
+                } else {
+                    Token::Equal
+                }
+            }
+            ',' => Token::Comma,
+            ';' => Token::Semicolon,
+            '\n' => Token::Newline,
+            ' ' | '\t' | '\r' => return None,
             _ => {
-                if current_char.is_ascii_digit() {
-                    self.scan_number();
+                if current_char.is_ascii_digit()
+                    || (current_char == '.' && self.look_ahead().is_ascii_digit())
+                {
+                    return Some(self.scan_number());
+                } else if Lexer::is_identifier_start(current_char) {
+                    self.scan_identifier()
                 } else {
-                    self.has_errors = true;
+                    return Some(Err(format!("Unexpected character '{current_char}'.")));
                 }
             }
-        }
+        };
+
+        Some(Ok(token))
     }
 
     /// Scans a number type of token.
-    fn scan_number(&mut self) {
-        while self.look_ahead().is_ascii_digit() {
-            self.advance();
+    /// Accepts decimals with optional fraction and exponent, `1_000.5e-3`, `.5`,
+    /// and integers in other bases, `0xFF`, `0b1010`, `0o17`.
+    fn scan_number(&mut self) -> Result<Token<'a>, String> {
+        let first = self.source[self.start..].chars().next().unwrap_or('\0');
+
+        // integers in other bases.
+        if first == '0' {
+            let base = match self.look_ahead() {
+                'x' | 'X' => Some((16, "hexadecimal")),
+                'b' | 'B' => Some((2, "binary")),
+                'o' | 'O' => Some((8, "octal")),
+                _ => None,
+            };
+
+            if let Some((radix, name)) = base {
+                self.advance();
+                return self.scan_radix_number(radix, name);
+            }
+        }
+
+        // integer part, unless the number starts with '.'.
+        if first != '.' {
+            self.scan_digits();
         }
 
         // for floating point numbers.
-        if self.look_ahead() == '.' && self.look_ahead_twice().is_ascii_digit() {
+        if first == '.' || (self.look_ahead() == '.' && self.look_ahead_twice().is_ascii_digit()) {
+            if first != '.' {
+                self.advance();
+            }
+            self.scan_digits();
+        }
+
+        // for scientific notation.
+        if matches!(self.look_ahead(), 'e' | 'E') {
+            let mantissa = self.current;
             self.advance();
-            while self.look_ahead().is_ascii_digit() {
+            if matches!(self.look_ahead(), '+' | '-') {
                 self.advance();
             }
+
+            if self.look_ahead().is_ascii_digit() {
+                self.scan_digits();
+            } else if self.juxtaposition {
+                // the number ends before the 'e', which starts a name.
+                self.current = mantissa;
+            } else {
+                return Err(format!(
+                    "Invalid number literal '{}', expected digits in the exponent.",
+                    self.literal()
+                ));
+            }
+        }
+
+        let literal = self.literal();
+        if !Lexer::valid_separators(literal) {
+            return Err(format!(
+                "Invalid number literal '{literal}', '_' must be between digits."
+            ));
         }
 
         // we take literal string of the number and parse it into rust's f64.
-        let number_literal = self.source_string[self.start..self.current]
-            .to_string()
-            .parse::<f64>();
+        match literal.replace('_', "").parse::<f64>() {
+            Ok(number_literal) => Ok(Token::Number(number_literal)),
+            Err(_) => Err(format!("Invalid number literal '{literal}'.")),
+        }
+    }
+
+    /// Scans an integer in the given base, the prefix must be already consumed.
+    /// # Arguments
+    /// * radix - base of the integer.
+    /// * name - name of the base, used in errors.
+    fn scan_radix_number(&mut self, radix: u32, name: &str) -> Result<Token<'a>, String> {
+        // we take every alphanumeric character so invalid digits are reported
+        // instead of silently becoming a new token.
+        while self.look_ahead().is_alphanumeric() || self.look_ahead() == '_' {
+            self.advance();
+        }
+
+        let literal = self.literal();
+        let digits = &literal[2..];
+
+        if digits.is_empty() {
+            return Err(format!(
+                "Invalid {name} literal '{literal}', expected digits after '{literal}'."
+            ));
+        }
+
+        let mut value = 0_f64;
+        for digit in digits.chars().filter(|c| *c != '_') {
+            match digit.to_digit(radix) {
+                Some(digit) => value = value * radix as f64 + digit as f64,
+                None => {
+                    return Err(format!(
+                        "Invalid digit '{digit}' in {name} literal '{literal}'."
+                    ))
+                }
+            }
+        }
 
-        match number_literal {
-            Ok(number_literal) => self.add_token(Token::Number(number_literal)),
-            // probably never going to happen but still you never know.
-            Err(_) => panic!("Failed to parse number literal as f64"),
+        if digits.starts_with('_') || digits.ends_with('_') {
+            return Err(format!(
+                "Invalid {name} literal '{literal}', '_' must be between digits."
+            ));
         }
+
+        Ok(Token::Number(value))
     }
 
-    /// consumes current character and returns it.
-    fn advance(&mut self) -> char {
-        self.current += 1;
-        self.source_chars[self.current - 1]
+    /// consumes digits and '_' separators.
+    fn scan_digits(&mut self) {
+        while self.look_ahead().is_ascii_digit() || self.look_ahead() == '_' {
+            self.advance();
+        }
     }
 
-    /// returns current character but doesn't consume it.
-    fn look_ahead(&mut self) -> char {
+    /// checks every '_' in a decimal literal is surrounded by digits or other separators.
+    fn valid_separators(literal: &str) -> bool {
+        let chars: Vec<char> = literal.chars().collect();
+        let is_digit = |index: Option<usize>| {
+            index
+                .and_then(|index| chars.get(index))
+                .is_some_and(|c| c.is_ascii_digit() || *c == '_')
+        };
+
+        !literal.ends_with('_')
+            && (0..chars.len())
+                .filter(|index| chars[*index] == '_')
+                .all(|index| is_digit(index.checked_sub(1)) && is_digit(Some(index + 1)))
+    }
+
+    /// Scans an identifier or a keyword.
+    fn scan_identifier(&mut self) -> Token<'a> {
+        while Lexer::is_identifier_part(self.look_ahead()) {
+            self.advance();
+        }
+
+        match self.literal() {
+            "let" => Token::Let,
+            "in" => Token::In,
+            identifier => Token::Identifier(identifier),
+        }
+    }
+
+    /// checks if a character can start an identifier.
+    pub(crate) fn is_identifier_start(c: char) -> bool {
+        (c.is_alphabetic() || c == '_') && c != 'π'
+    }
+
+    /// checks if a character can continue an identifier,
+    /// 'π' is always a token on its own so `2πr` works with implicit multiplication.
+    pub(crate) fn is_identifier_part(c: char) -> bool {
+        (c.is_alphanumeric() || c == '_') && c != 'π'
+    }
+
+    /// returns text of the token being scanned, borrowed from the source.
+    fn literal(&self) -> &'a str {
+        &self.source[self.start..self.current]
+    }
+
+    /// consumes current character and returns it.
+    fn advance(&mut self) -> char {
         if self.is_at_end() {
             return '\0';
         }
-        self.source_chars[self.current]
+
+        let current_char = self.look_ahead();
+        self.current += current_char.len_utf8();
+        current_char
     }
 
-    /// returns next character but doesn't consume it.
-    fn look_ahead_twice(&mut self) -> char {
-        if self.current + 1 >= self.len {
-            return '\0';
+    /// consumes current character only if it is the expected one.
+    fn advance_if(&mut self, expected: char) -> bool {
+        if self.look_ahead() != expected {
+            return false;
         }
-        self.source_chars[self.current + 1]
+        self.advance();
+        true
     }
 
-    /// returns the next token, and also consumes it.
-    pub fn next_token(&mut self) -> Token {
-        self.tokens.pop().unwrap_or(Token::Eof)
+    /// returns current character but doesn't consume it.
+    fn look_ahead(&self) -> char {
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
-    /// returns the next token, but doesn't consume it.
-    pub fn peek(&self) -> Token {
-        self.tokens.last().copied().unwrap_or(Token::Eof)
+    /// returns next character but doesn't consume it.
+    fn look_ahead_twice(&self) -> char {
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
     // checks if reached the end of the input string.
     fn is_at_end(&self) -> bool {
-        self.current >= self.len
+        self.current >= self.source.len()
     }
+}
+
+/// Scans tokens one at a time, the iterator ends at the end of the source string.
+/// Scanning continues after an error, so every invalid token is reported.
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<SpannedToken<'a>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_at_end() {
+            self.start = self.current;
+
+            let scanned = self.scan_token();
+            let span = Span {
+                start: self.start,
+                end: self.current,
+            };
+
+            match scanned {
+                // whitespace, keep scanning.
+                None => continue,
+                Some(Ok(token)) => {
+                    log::trace!("[expr-solver] scanned token={}", token);
+                    return Some(Ok(SpannedToken { token, span }));
+                }
+                Some(Err(message)) => {
+                    log::trace!("[expr-solver] lexical error={}", message);
+                    return Some(Err(LexError { message, span }));
+                }
+            }
+        }
 
-    // helper function to add tokens.
-    fn add_token(&mut self, token_type: Token) {
-        log::trace!("[expr-solver] adding token={}", token_type);
-        self.tokens.push(token_type);
+        None
     }
 }
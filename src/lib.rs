@@ -1,13 +1,31 @@
 pub mod ast;
+pub mod binary;
+pub mod diagnostic;
+pub mod dot;
+pub mod environment;
 pub mod interpreter;
+pub mod latex;
 pub mod lexer;
+pub mod limits;
+pub mod mathml;
+pub mod observer;
 pub mod parser;
+pub mod printer;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod sexpr;
 pub mod token;
+pub mod trace;
 pub mod utils;
+pub mod value;
+pub mod visit;
 
+use ast::{Statement, AST};
+use diagnostic::Diagnostic;
 use interpreter::Interpreter;
 use lexer::Lexer;
-use parser::Parser;
+use parser::{ImplicitMultiplication, Parser, ParserOptions};
+use value::Value;
 
 /// Takes mathematical expression as string, resolves it.
 /// # Arguments
@@ -34,37 +52,209 @@ use parser::Parser;
 /// // binary and unary in one expression.
 /// assert_eq!(resolve("2+-2".to_string()), Ok(0.0));
 ///
+/// // define functions and bind values.
+/// assert_eq!(resolve("f(x) = x^2 + 1; f(3) + f(4)".to_string()), Ok(27.0));
+/// assert_eq!(resolve("let r = 2 in r^2 * 3".to_string()), Ok(12.0));
+///
+/// // lambdas and higher order functions over lists.
+/// assert_eq!(
+///     resolve("reduce((a, b) => a + b, map(x => x * 2, [1, 2, 3]))".to_string()),
+///     Ok(12.0)
+/// );
+///
 /// // gives syntax error.
 /// assert!(matches!(resolve("2&2".to_string()), Err(String)));
+///
+/// // the whole input must be an expression.
+/// assert_eq!(
+///     resolve("2)2".to_string()),
+///     Err("1..2: Unexpected token after end of expression : )".to_string())
+/// );
 /// ```
 pub fn resolve(input_string: String) -> Result<f64, String> {
+    resolve_with_options(input_string, ParserOptions::default())
+}
+
+/// Same as `resolve`, but parses the expression with non default options.
+/// # Arguments
+/// * input_string
+/// * options - options for the parser.
+/// # Returns
+/// Result enum with solved value or incase of an error, error string.
+/// # Examples
+/// ```
+/// use expr_solver::resolve_with_options;
+/// use expr_solver::parser::{ImplicitMultiplication, ParserOptions};
+///
+/// let options = ParserOptions {
+///     implicit_multiplication: ImplicitMultiplication::Tighter,
+///     ..Default::default()
+/// };
+/// assert_eq!(resolve_with_options("2(3+4)".to_string(), options), Ok(14.0));
+/// assert_eq!(resolve_with_options("1/2(2)".to_string(), options), Ok(0.25));
+/// ```
+pub fn resolve_with_options(input_string: String, options: ParserOptions) -> Result<f64, String> {
     log::debug!("[expr-resolve] input_string={}", &input_string);
 
-    // create a new lexer
-    // and parse input string into tokens.
-    let mut lexer = Lexer::new(&input_string);
-    lexer.scan();
+    let statements = parse_with_options(&input_string, options).map_err(describe)?;
 
-    if lexer.has_errors {
-        log::debug!(
-            "[expr-resolve] Found lexical errors in expression : {}",
-            &input_string
-        );
-        return Err("Found lexical error(s) in the expression.".to_string());
-    }
+    // we run the statements with our interpreter.
+    Interpreter::new().run(&statements)?.as_number()
+}
 
-    // create a new parser
-    let mut parser = Parser::new(&mut lexer);
-    // and parse tokens into AST.
-    let ast = parser.parse();
+/// Takes a script of statements separated by ';' or new lines, resolves each one.
+/// Assignments, `x = 3`, define variables for following statements and
+/// `ans` always holds the value of the last expression.
+/// # Arguments
+/// * input_string
+/// # Returns
+/// Result enum with value of every statement or incase of an error, error string.
+/// # Examples
+/// ```
+/// use expr_solver::{interpreter::Interpreter, parse, resolve_script};
+///
+/// let values = resolve_script("r = 2\npi * r^2; ans / pi".to_string()).unwrap();
+/// assert_eq!(values[2].as_number(), Ok(4.0));
+///
+/// // keep an interpreter around to build up a computation step by step.
+/// let mut interpreter = Interpreter::new();
+/// interpreter.run_script(&parse("x = 3").unwrap()).unwrap();
+/// let values = interpreter.run_script(&parse("x * 2; ans + 1").unwrap()).unwrap();
+/// assert_eq!(values[1].as_number(), Ok(7.0));
+/// ```
+pub fn resolve_script(input_string: String) -> Result<Vec<Value>, String> {
+    log::debug!("[expr-resolve] script input_string={}", &input_string);
+
+    let statements = parse(&input_string).map_err(describe)?;
+
+    Interpreter::new()
+        .run_script(&statements)
+        .map_err(String::from)
+}
 
-    // if the ast is correct.
-    match ast {
-        Ok(ast) => {
-            // we walk the ast with our interpreter.
-            Interpreter::walk_ast(&ast)
-        }
-        // otherwise we return the error we got from the parser.
-        Err(err) => Err(err),
+/// Takes a program as string, parses it into statements.
+/// # Arguments
+/// * input_string
+/// # Returns
+/// Result enum with the statements of the program or incase of errors,
+/// every problem found in the program along with where it is.
+/// # Examples
+/// ```
+/// use expr_solver::parse;
+///
+/// let diagnostics = parse("2 & 3 $ 4; f(1 2)\nlet = 3").unwrap_err();
+/// let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+/// assert_eq!(
+///     messages,
+///     [
+///         "Unexpected character '&'.",
+///         "Unexpected character '$'.",
+///         "Expected operator recieved number : 2",
+///         "Expected name after 'let'.",
+///     ]
+/// );
+/// assert_eq!(diagnostics[1].span.start, 6);
+/// ```
+pub fn parse(input_string: &str) -> Result<Vec<Statement>, Vec<Diagnostic>> {
+    parse_with_options(input_string, ParserOptions::default())
+}
+
+/// Same as `parse`, but with non default options for the parser.
+/// # Arguments
+/// * input_string
+/// * options - options for the parser.
+/// # Returns
+/// Result enum with the statements of the program or incase of errors,
+/// every problem found in the program along with where it is.
+pub fn parse_with_options(
+    input_string: &str,
+    options: ParserOptions,
+) -> Result<Vec<Statement>, Vec<Diagnostic>> {
+    // create a new lexer, which scans tokens lazily,
+    // and a new parser pulling tokens from it.
+    let lexer = lexer_with_options(input_string, options);
+    let mut parser = Parser::with_options(lexer, options);
+    // and parse tokens into statements.
+    parser.parse_program()
+}
+
+/// Takes a single expression as string, parses it into an ast.
+/// # Arguments
+/// * input_string
+/// # Returns
+/// Result enum with the ast of the expression or incase of an error,
+/// the first problem found along with where it is.
+/// # Examples
+/// ```
+/// use expr_solver::{ast::{BinaryOperator, AST}, parse_expression};
+///
+/// assert_eq!(
+///     parse_expression("x + 1"),
+///     Ok(AST::binary(BinaryOperator::Add, AST::Var("x".to_string()), AST::Literal(1.0)))
+/// );
+///
+/// // statements and programs are not expressions.
+/// assert!(parse_expression("x = 1").is_err());
+/// assert!(parse_expression("1; 2").is_err());
+/// ```
+pub fn parse_expression(input_string: &str) -> Result<AST, Diagnostic> {
+    let lexer = Lexer::new(input_string);
+    let mut parser = Parser::new(lexer);
+    // the expression must take up the whole string.
+    parser.parse()
+}
+
+/// Parses an expression at the start of a string, stopping at the first token
+/// which can't continue it, so expressions can be embedded in larger text.
+/// Text which is not a valid token also ends the expression.
+/// # Arguments
+/// * input_string
+/// * options - options for the parser.
+/// # Returns
+/// Result enum with the ast of the expression and how many bytes of the string
+/// it took up, or incase of an error, the problem along with where it is.
+/// # Examples
+/// ```
+/// use expr_solver::{interpreter::Interpreter, parse_prefix, parser::ParserOptions};
+///
+/// let input = "2 * (3 + 4) apples";
+/// let (ast, consumed) = parse_prefix(input, ParserOptions::default()).unwrap();
+/// assert_eq!(&input[..consumed], "2 * (3 + 4)");
+/// assert_eq!(Interpreter::walk_ast(&ast), Ok(14.0));
+///
+/// let (_, consumed) = parse_prefix("1 + 2} items", ParserOptions::default()).unwrap();
+/// assert_eq!(consumed, 5);
+/// ```
+pub fn parse_prefix(
+    input_string: &str,
+    options: ParserOptions,
+) -> Result<(AST, usize), Diagnostic> {
+    // tokens end at the first invalid text, it can't be part of the expression.
+    let lexer = lexer_with_options(input_string, options).take_while(|token| token.is_ok());
+    let mut parser = Parser::with_options(lexer, options);
+    parser.parse_prefix()
+}
+
+/// Creates a lexer scanning numbers the way the parser options need.
+/// # Arguments
+/// * input_string
+/// * options - options for the parser.
+/// # Returns
+/// * Lexer - the lexer, scanning numbers followed by names if multiplication
+///   by juxtaposition is enabled.
+fn lexer_with_options(input_string: &str, options: ParserOptions) -> Lexer<'_> {
+    let lexer = Lexer::new(input_string);
+    match options.implicit_multiplication {
+        ImplicitMultiplication::Disabled => lexer,
+        _ => lexer.with_juxtaposition(),
     }
 }
+
+/// Joins diagnostics into a single error string, one line per diagnostic.
+fn describe(diagnostics: Vec<Diagnostic>) -> String {
+    diagnostics
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,87 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::interpreter::MAX_CALL_DEPTH;
+
+/// Limits on the resources a single evaluation can use, so formulas from
+/// untrusted users can't hang or exhaust a shared server.
+/// Every limit applies to one call of `Interpreter::evaluate`, counting the
+/// work done by the functions it calls.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    // maximum number of nodes evaluated, unlimited by default.
+    pub max_operations: Option<u64>,
+    // maximum depth of nested function calls, `MAX_CALL_DEPTH` by default.
+    // calls nest on the stack, so deeper limits are capped to `MAX_CALL_DEPTH`.
+    pub max_call_depth: usize,
+    // maximum wall time, unlimited by default.
+    pub max_time: Option<Duration>,
+    // maximum magnitude of a number computed along the way, unlimited by default.
+    // infinite results exceed any limit.
+    pub max_magnitude: Option<f64>,
+    // maximum number of items of a list built along the way, unlimited by default.
+    pub max_list_length: Option<usize>,
+    // cancels the evaluation once set, from any thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_operations: None,
+            max_call_depth: MAX_CALL_DEPTH,
+            max_time: None,
+            max_magnitude: None,
+            max_list_length: None,
+            cancel: None,
+        }
+    }
+}
+
+/// The limit an evaluation ran into, along with its value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LimitExceeded {
+    Operations(u64),
+    CallDepth(usize),
+    // nesting of expressions, set with `Interpreter::with_max_depth`.
+    Nesting(usize),
+    Time(Duration),
+    Magnitude(f64),
+    ListLength(usize),
+    Cancelled,
+}
+
+impl core::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LimitExceeded::Operations(max) => {
+                write!(f, "Evaluation exceeded the limit of {max} operations.")
+            }
+            LimitExceeded::CallDepth(max) => {
+                write!(
+                    f,
+                    "Maximum call depth of {max} exceeded, recursive definition?"
+                )
+            }
+            LimitExceeded::Nesting(max) => {
+                write!(
+                    f,
+                    "Expression is nested too deeply, maximum depth is {max}."
+                )
+            }
+            LimitExceeded::Time(max) => {
+                write!(f, "Evaluation exceeded the time limit of {max:?}.")
+            }
+            LimitExceeded::Magnitude(max) => {
+                write!(f, "Value exceeded the maximum magnitude of {max}.")
+            }
+            LimitExceeded::ListLength(max) => {
+                write!(f, "List exceeded the maximum length of {max}.")
+            }
+            LimitExceeded::Cancelled => write!(f, "Evaluation was cancelled."),
+        }
+    }
+}
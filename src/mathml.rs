@@ -0,0 +1,288 @@
+use crate::ast::{BinaryOperator, PostfixOperator, UnaryOperator, AST};
+use crate::printer::{scientific, typeset_parens, Operand};
+
+const GREEK_LETTERS: [(&str, char); 24] = [
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("omicron", 'ο'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+];
+
+// what is left to render of an ast.
+enum Piece<'t> {
+    Ast(&'t AST),
+    Text(&'static str),
+}
+
+/// Renders an expression as Presentation MathML, so browsers can show it
+/// without a JavaScript math library.
+/// Division becomes `<mfrac>`, powers `<msup>`, roots `<msqrt>`, and every
+/// node becomes a single element, with `<mrow>` around anything made of
+/// more than one, grouping operands in parentheses only where precedence needs it.
+/// # Arguments
+/// * ast - the expression.
+/// # Returns
+/// * String - the `<math>` element.
+/// # Examples
+/// ```
+/// use expr_solver::{mathml::to_mathml, parse_expression};
+///
+/// let ast = parse_expression("-n! / 2").unwrap();
+/// assert_eq!(
+///     to_mathml(&ast),
+///     concat!(
+///         r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#,
+///         "<mfrac><mrow><mo>−</mo><mrow><mi>n</mi><mo>!</mo></mrow></mrow><mn>2</mn></mfrac>",
+///         "</math>"
+///     )
+/// );
+/// ```
+pub fn to_mathml(ast: &AST) -> String {
+    let mut out = String::from(r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#);
+    let mut pending = vec![Piece::Text("</math>"), Piece::Ast(ast)];
+
+    while let Some(piece) = pending.pop() {
+        let ast = match piece {
+            Piece::Text(text) => {
+                out.push_str(text);
+                continue;
+            }
+            Piece::Ast(ast) => ast,
+        };
+
+        // pieces are pushed in reverse, so they are rendered in order.
+        match ast {
+            AST::Literal(n) => out.push_str(&number(*n)),
+            AST::Var(name) => out.push_str(&identifier(name)),
+            AST::Unary { op, operand } => match op {
+                UnaryOperator::Root => {
+                    out.push_str("<msqrt>");
+                    pending.push(Piece::Text("</msqrt>"));
+                    pending.push(Piece::Ast(operand));
+                }
+                _ => {
+                    let ((), right_bp) = op.binding_power();
+                    out.push_str("<mrow>");
+                    out.push_str(match op {
+                        UnaryOperator::Negate => "<mo>−</mo>",
+                        _ => "<mo>+</mo>",
+                    });
+                    pending.push(Piece::Text("</mrow>"));
+                    push_operand(&mut pending, operand, Operand::Right(right_bp));
+                }
+            },
+            AST::Postfix { op, operand } => {
+                out.push_str("<mrow>");
+                pending.push(Piece::Text("</mrow>"));
+                pending.push(Piece::Text(match op {
+                    PostfixOperator::Factorial => "<mo>!</mo>",
+                }));
+                push_operand(&mut pending, operand, Operand::Base);
+            }
+            AST::Binary { op, lhs, rhs } => match op {
+                BinaryOperator::Divide => {
+                    out.push_str("<mfrac>");
+                    pending.push(Piece::Text("</mfrac>"));
+                    pending.push(Piece::Ast(rhs));
+                    pending.push(Piece::Ast(lhs));
+                }
+                BinaryOperator::Power => {
+                    out.push_str("<msup>");
+                    pending.push(Piece::Text("</msup>"));
+                    pending.push(Piece::Ast(rhs));
+                    push_operand(&mut pending, lhs, Operand::Base);
+                }
+                _ => {
+                    let (left_bp, right_bp) = op.binding_power();
+                    out.push_str("<mrow>");
+                    pending.push(Piece::Text("</mrow>"));
+                    push_operand(&mut pending, rhs, Operand::Right(right_bp));
+                    pending.push(Piece::Text(operator(*op)));
+                    push_operand(&mut pending, lhs, Operand::Left(left_bp));
+                }
+            },
+            AST::Call(name, arguments) => match name.as_str() {
+                "sqrt" if arguments.len() == 1 => {
+                    out.push_str("<msqrt>");
+                    pending.push(Piece::Text("</msqrt>"));
+                    pending.push(Piece::Ast(&arguments[0]));
+                }
+                "abs" if arguments.len() == 1 => {
+                    push_items(&mut pending, "<mo>|</mo>", arguments, "<mo>|</mo>");
+                }
+                _ => {
+                    // the invisible function application operator, `sin x` isn't `sin * x`.
+                    out.push_str("<mrow>");
+                    out.push_str(&function(name));
+                    out.push_str("<mo>&#x2061;</mo>");
+                    pending.push(Piece::Text("</mrow>"));
+                    push_items(&mut pending, "<mo>(</mo>", arguments, "<mo>)</mo>");
+                }
+            },
+            AST::List(items) => push_items(&mut pending, "<mo>[</mo>", items, "<mo>]</mo>"),
+            AST::Let(name, value, body) => {
+                out.push_str("<mrow><mtext>let</mtext>");
+                out.push_str(&identifier(name));
+                out.push_str("<mo>=</mo>");
+                pending.push(Piece::Text("</mrow>"));
+                pending.push(Piece::Ast(body));
+                pending.push(Piece::Text("<mtext>in</mtext>"));
+                pending.push(Piece::Ast(value));
+            }
+            AST::Lambda(parameters, body) => {
+                out.push_str("<mrow>");
+                match parameters.as_slice() {
+                    [parameter] => out.push_str(&identifier(parameter)),
+                    _ => {
+                        let parameters: Vec<String> =
+                            parameters.iter().map(|p| identifier(p)).collect();
+                        out.push_str("<mrow><mo>(</mo>");
+                        out.push_str(&parameters.join("<mo>,</mo>"));
+                        out.push_str("<mo>)</mo></mrow>");
+                    }
+                }
+                out.push_str("<mo>↦</mo>");
+                pending.push(Piece::Text("</mrow>"));
+                pending.push(Piece::Ast(body));
+            }
+        }
+    }
+
+    out
+}
+
+/// Pushes an operand, wrapped in parentheses if needed.
+/// # Arguments
+/// * pending - pieces left to render.
+/// * operand - the operand.
+/// * position - where the operand sits.
+fn push_operand<'t>(pending: &mut Vec<Piece<'t>>, operand: &'t AST, position: Operand) {
+    if typeset_parens(operand, position) {
+        pending.push(Piece::Text("<mo>)</mo></mrow>"));
+        pending.push(Piece::Ast(operand));
+        pending.push(Piece::Text("<mrow><mo>(</mo>"));
+    } else {
+        pending.push(Piece::Ast(operand));
+    }
+}
+
+/// Pushes items in a row between delimiters, separated by commas.
+/// # Arguments
+/// * pending - pieces left to render.
+/// * open - the opening delimiter.
+/// * items - the items.
+/// * close - the closing delimiter.
+fn push_items<'t>(
+    pending: &mut Vec<Piece<'t>>,
+    open: &'static str,
+    items: &'t [AST],
+    close: &'static str,
+) {
+    pending.push(Piece::Text("</mrow>"));
+    pending.push(Piece::Text(close));
+    for (i, item) in items.iter().enumerate().rev() {
+        pending.push(Piece::Ast(item));
+        if i > 0 {
+            pending.push(Piece::Text("<mo>,</mo>"));
+        }
+    }
+    pending.push(Piece::Text(open));
+    pending.push(Piece::Text("<mrow>"));
+}
+
+/// Returns the MathML of an infix operator.
+fn operator(op: BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => "<mo>+</mo>",
+        BinaryOperator::Subtract => "<mo>−</mo>",
+        BinaryOperator::Multiply => "<mo>⋅</mo>",
+        BinaryOperator::Divide => "<mo>/</mo>",
+        BinaryOperator::Power => "<mo>^</mo>",
+        BinaryOperator::Less => "<mo>&lt;</mo>",
+        BinaryOperator::LessEqual => "<mo>≤</mo>",
+        BinaryOperator::Greater => "<mo>&gt;</mo>",
+        BinaryOperator::GreaterEqual => "<mo>≥</mo>",
+        BinaryOperator::Equal => "<mo>=</mo>",
+        BinaryOperator::NotEqual => "<mo>≠</mo>",
+    }
+}
+
+/// Renders the name of a variable, greek letters become their symbols.
+fn identifier(name: &str) -> String {
+    match GREEK_LETTERS.iter().find(|(greek, _)| *greek == name) {
+        Some((_, letter)) => format!("<mi>{letter}</mi>"),
+        None => format!("<mi>{}</mi>", escape(name)),
+    }
+}
+
+/// Renders the name of a function, inverse trigonometric ones as they are written in maths.
+fn function(name: &str) -> String {
+    match name {
+        "asin" | "acos" | "atan" => format!("<mi>arc{}</mi>", &name[1..]),
+        _ => format!("<mi>{}</mi>", escape(name)),
+    }
+}
+
+/// Renders a number, very large and very small ones in scientific notation.
+fn number(n: f64) -> String {
+    if n.is_nan() {
+        return "<mi>NaN</mi>".to_string();
+    }
+
+    let sign = if n.is_sign_negative() {
+        "<mo>−</mo>"
+    } else {
+        ""
+    };
+    let magnitude = n.abs();
+
+    let digits = if magnitude.is_infinite() {
+        "<mi>∞</mi>".to_string()
+    } else if scientific(magnitude) {
+        let scientific = format!("{magnitude:e}");
+        let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+        let exponent = match exponent.strip_prefix('-') {
+            Some(exponent) => format!("<mrow><mo>−</mo><mn>{exponent}</mn></mrow>"),
+            None => format!("<mn>{exponent}</mn>"),
+        };
+        let power = format!("<msup><mn>10</mn>{exponent}</msup>");
+        match mantissa {
+            "1" => power,
+            _ => format!("<mrow><mn>{mantissa}</mn><mo>×</mo>{power}</mrow>"),
+        }
+    } else {
+        format!("<mn>{magnitude}</mn>")
+    };
+
+    match sign {
+        "" => digits,
+        _ => format!("<mrow>{sign}{digits}</mrow>"),
+    }
+}
+
+/// Escapes the characters XML gives a meaning to.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,60 @@
+use crate::ast::AST;
+use crate::trace::Operation;
+use crate::value::Value;
+
+/// Hooks an interpreter calls while evaluating, to build profiling, coverage
+/// or auditing on top of it. Every method does nothing by default, so an
+/// observer only overrides the events it cares about.
+/// Nodes are entered before their children and left after them, nodes inside
+/// the body of a called function included. A node whose evaluation fails is
+/// entered but never left.
+/// # Examples
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use expr_solver::{
+///     ast::AST, interpreter::Interpreter, observer::Observer, parse, trace::Operation,
+///     value::Value,
+/// };
+///
+/// // counts how many times each function is called.
+/// #[derive(Default)]
+/// struct Calls(Vec<(String, usize)>);
+///
+/// impl Observer for Calls {
+///     fn apply(&mut self, _: &AST, operation: &Operation, operands: &[Value], _: &Value) {
+///         if let Operation::Call(name) = operation {
+///             self.0.push((name.clone(), operands.len()));
+///         }
+///     }
+/// }
+///
+/// let calls = Rc::new(RefCell::new(Calls::default()));
+/// let mut interpreter = Interpreter::new().with_observer(calls.clone());
+/// interpreter.run(&parse("f(x) = abs(x); f(-1) + sqrt(4)").unwrap()).unwrap();
+///
+/// assert_eq!(
+///     calls.borrow().0,
+///     [("abs".to_string(), 1), ("f".to_string(), 1), ("sqrt".to_string(), 1)]
+/// );
+/// ```
+pub trait Observer {
+    /// Called before a node is evaluated.
+    /// # Arguments
+    /// * ast - the node.
+    fn enter(&mut self, _ast: &AST) {}
+
+    /// Called once a node is evaluated.
+    /// # Arguments
+    /// * ast - the node.
+    /// * value - value of the node.
+    fn leave(&mut self, _ast: &AST, _value: &Value) {}
+
+    /// Called when an operator or a function is applied to its operands,
+    /// after they are evaluated and before the node is left.
+    /// # Arguments
+    /// * ast - the node.
+    /// * operation - the applied operator or the called function.
+    /// * operands - values of the operands or arguments, in order.
+    /// * result - the value it gave.
+    fn apply(&mut self, _ast: &AST, _operation: &Operation, _operands: &[Value], _result: &Value) {}
+}
@@ -1,27 +1,180 @@
+use std::iter::Fuse;
+use std::mem;
+use std::rc::Rc;
 use std::vec;
 
-use crate::ast::AST;
-use crate::lexer::Lexer;
-use crate::token::Token;
+use crate::ast::{BinaryOperator, PostfixOperator, Statement, UnaryOperator, AST};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::LexError;
+use crate::token::{Span, SpannedToken, Token};
+
+/// How the parser treats juxtaposed operands, `2x`, `2(3+4)` or `(a+b)(a-b)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImplicitMultiplication {
+    // juxtaposition is not allowed.
+    #[default]
+    Disabled,
+    // same precedence as '*', `1/2x` is `(1/2)*x`.
+    SameAsExplicit,
+    // binds tighter than '*' and '/', `1/2x` is `1/(2*x)`.
+    Tighter,
+}
+
+/// Default maximum nesting depth of an expression,
+/// small enough to parse safely on a 2 MiB thread stack in debug builds.
+pub const MAX_DEPTH: usize = 128;
+
+/// Options to change what the parser accepts.
+#[derive(Debug, Clone, Copy)]
+pub struct ParserOptions {
+    // multiplication by juxtaposition, disabled by default.
+    // when enabled `2e` and `2exp(1)` multiply by a name, `2e3` stays a number.
+    pub implicit_multiplication: ImplicitMultiplication,
+    // maximum nesting depth of an expression, deeper expressions are rejected
+    // instead of overflowing the stack. Only nesting which makes the parser
+    // recurse counts, long chains like `1 + 1 + ... + 1` are limited only by memory.
+    pub max_depth: usize,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            implicit_multiplication: ImplicitMultiplication::default(),
+            max_depth: MAX_DEPTH,
+        }
+    }
+}
 
 // Top level parser.
-// parses linear array of tokens into AST.
-pub struct Parser<'a> {
-    lexer: &'a mut Lexer,
+// parses a stream of tokens into AST, pulling one token at a time
+// from any token iterator with one token of lookahead.
+pub struct Parser<'a, I>
+where
+    I: Iterator<Item = Result<SpannedToken<'a>, LexError>>,
+{
+    // fused, iterators may do anything once they returned None.
+    tokens: Fuse<I>,
+    // the next token, if it has been peeked.
+    peeked: Option<SpannedToken<'a>>,
+    // span of the last token seen, errors point at it.
+    span: Span,
+    // the last consumed token, None if it was invalid text.
+    previous: Option<Token<'a>>,
+    // current depth of nested expressions being parsed.
+    depth: usize,
+    // byte offset right after the last consumed token.
+    consumed: usize,
+    // parsing a prefix, any token which can't continue the expression ends it.
+    prefix: bool,
+    options: ParserOptions,
 }
 
-impl<'a> Parser<'a> {
+impl<'a, I> Parser<'a, I>
+where
+    I: Iterator<Item = Result<SpannedToken<'a>, LexError>>,
+{
     // constructor for parser.
-    pub fn new(lexer: &'a mut Lexer) -> Self {
-        log::debug!("[expr-solver] Creating new parser instance.");
-        Self { lexer }
+    pub fn new(tokens: I) -> Self {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    // constructor for parser with non default options.
+    pub fn with_options(tokens: I, options: ParserOptions) -> Self {
+        log::debug!("[expr-solver] Creating new parser instance. options={options:?}");
+        Self {
+            tokens: tokens.fuse(),
+            peeked: None,
+            span: Span::default(),
+            previous: None,
+            depth: 0,
+            consumed: 0,
+            prefix: false,
+            options,
+        }
+    }
+
+    /// returns the next token, and also consumes it.
+    /// # Returns
+    /// The token, Eof once the tokens run out, otherwise the lexical error.
+    fn next_token(&mut self) -> Result<Token<'a>, Diagnostic> {
+        let token = self.peek()?;
+        if let Some(spanned) = self.peeked.take() {
+            self.consumed = spanned.span.end;
+        }
+        self.previous = Some(token);
+        Ok(token)
+    }
+
+    /// returns the next token, but doesn't consume it.
+    /// Lexical errors are consumed as soon as they are seen, so each one is reported once.
+    /// # Returns
+    /// The token, Eof once the tokens run out, otherwise the lexical error.
+    fn peek(&mut self) -> Result<Token<'a>, Diagnostic> {
+        if let Some(spanned) = self.peeked {
+            return Ok(spanned.token);
+        }
+
+        match self.tokens.next() {
+            Some(Ok(spanned)) => {
+                self.span = spanned.span;
+                self.peeked = Some(spanned);
+                Ok(spanned.token)
+            }
+            Some(Err(error)) => {
+                self.span = error.span;
+                self.previous = None;
+                Err(Diagnostic::from(error))
+            }
+            None => {
+                // point right after the last token.
+                self.span = Span {
+                    start: self.span.end,
+                    end: self.span.end,
+                };
+                Ok(Token::Eof)
+            }
+        }
+    }
+
+    /// creates an error pointing at the last token seen.
+    fn error(&self, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            message: message.into(),
+            span: self.span,
+        }
+    }
+
+    /// Skips tokens till the end of the current statement, so parsing can
+    /// continue with the next one. Lexical errors found on the way are
+    /// recorded, errors of the parser would only be caused by the first one.
+    /// # Arguments
+    /// * diagnostics - where to record the lexical errors.
+    fn synchronize(&mut self, diagnostics: &mut Vec<Diagnostic>) {
+        // the failing statement might have already consumed its separator.
+        if matches!(self.previous, Some(Token::Semicolon | Token::Newline)) {
+            return;
+        }
+
+        loop {
+            match self.next_token() {
+                Ok(Token::Semicolon | Token::Newline | Token::Eof) => return,
+                Ok(_) => continue,
+                Err(diagnostic) => diagnostics.push(diagnostic),
+            }
+        }
     }
 
     // public parse method.
-    pub fn parse(&mut self) -> Result<AST, String> {
+    // the expression must take up all of the tokens.
+    pub fn parse(&mut self) -> Result<AST, Diagnostic> {
         log::debug!("[expr-solver] starting parsing.");
         // we start with binding power of 0.
-        match self.expr(0) {
+        let result = self.expr(0).and_then(|ast| {
+            self.expect_end()?;
+            Ok(ast)
+        });
+
+        match result {
             Ok(ast) => {
                 log::debug!("Parsed to ast:\n{}", ast);
                 Ok(ast)
@@ -33,55 +186,178 @@ impl<'a> Parser<'a> {
         }
     }
 
-    /// Parses an expression using Operator-Precedence parse (Pratt Parsing)
-    /// ref : https://en.wikipedia.org/wiki/Operator-precedence_parser
-    /// # Arguments
-    /// * min_binding_power - minimum binding power till recursivel parse the expression.
+    /// Parses an expression from the start of the tokens, stopping at the first
+    /// token which can't continue it, instead of requiring the tokens to end there.
+    /// Useful for expressions embedded in larger text.
     /// # Returns
-    /// * AST - ast of the expression.
-    fn expr(&mut self, min_binding_power: u8) -> Result<AST, String> {
-        // Parsing left hand side of the expression.
-        let mut left_hand_side = match self.lexer.next_token() {
-            // if the token is a number we simply create a node out of it.
-            Token::Number(f) => AST::Node(Token::Number(f)),
+    /// * (AST, usize) - ast of the expression and the byte offset right after
+    ///   its last token, which is how many bytes of the source it took up.
+    pub fn parse_prefix(&mut self) -> Result<(AST, usize), Diagnostic> {
+        log::debug!("[expr-solver] starting parsing prefix.");
+        self.prefix = true;
+        let ast = self.expr(0)?;
 
-            // if we reached the end we panic.
-            Token::Eof => return Err("Unexpected token : EOF".to_string()),
+        log::debug!("Parsed prefix of {} bytes to ast:\n{}", self.consumed, ast);
+        Ok((ast, self.consumed))
+    }
 
-            // if grouping, the AST can be treated as primary expression.
-            Token::LeftParen => {
-                let lhs = match self.expr(0) {
-                    Ok(lhs) => lhs,
-                    Err(e) => return Err(e),
-                };
+    /// checks there are no tokens left after the expression.
+    fn expect_end(&mut self) -> Result<(), Diagnostic> {
+        match self.peek()? {
+            Token::Eof => Ok(()),
+            token => Err(self.trailing(token)),
+        }
+    }
 
-                if !matches!(self.lexer.next_token(), Token::RightParen) {
-                    return Err("Expected ')' after expression.".to_string());
-                }
+    /// creates an error for a token left over after a complete expression,
+    /// the token must be the last one peeked.
+    fn trailing(&self, token: Token) -> Diagnostic {
+        self.error(format!(
+            "Unexpected token after end of expression : {}",
+            token.to_string().trim()
+        ))
+    }
+
+    /// Parses a program, a sequence of statements separated by ';' or new lines.
+    /// Every statement is either a function definition, `f(x) = x + 1`,
+    /// an assignment, `x = 3`, or an expression.
+    /// After an error parsing continues from the next statement, so every
+    /// problem in the program is reported at once.
+    /// # Returns
+    /// * `Vec<Statement>` - statements of the program in order.
+    /// * `Vec<Diagnostic>` - otherwise every error found, in order.
+    pub fn parse_program(&mut self) -> Result<Vec<Statement>, Vec<Diagnostic>> {
+        log::debug!("[expr-solver] starting parsing program.");
+        let mut statements = vec![];
+        let mut diagnostics = vec![];
 
-                lhs
+        loop {
+            let first = statements.is_empty() && diagnostics.is_empty();
+            match self.statement_in_program(first) {
+                Ok(Some(statement)) => statements.push(statement),
+                Ok(None) => break,
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    self.synchronize(&mut diagnostics);
+                    continue;
+                }
             }
 
-            // if its a operator, then it means the operator is a unary.
-            operator => {
-                // we get the right binding power of the unary operator.
-                let ((), right_binding_power) = Parser::prefix_binding_power(operator);
-
-                // then recursively parse it.
-                match self.expr(right_binding_power) {
-                    // save if its safe.
-                    Ok(right_hand_side) => AST::Con(operator, vec![right_hand_side]),
-                    // return if err.
-                    Err(err) => return Err(err),
+            // a statement must be followed by a separator to continue the program.
+            match self.peek() {
+                Ok(Token::Semicolon | Token::Newline) => continue,
+                Ok(Token::Eof) => break,
+                Ok(token) => {
+                    diagnostics.push(self.trailing(token));
+                    self.synchronize(&mut diagnostics);
                 }
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    self.synchronize(&mut diagnostics);
+                }
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            log::debug!("Failed to parse program, {} error(s).", diagnostics.len());
+            return Err(diagnostics);
+        }
+
+        log::debug!("Parsed {} statement(s).", statements.len());
+        Ok(statements)
+    }
+
+    /// Parses the next statement of a program, skipping empty statements.
+    /// # Arguments
+    /// * first - if this is the first statement, a program needs at least one.
+    /// # Returns
+    /// * `Option<Statement>` - the statement, None at the end of the program.
+    fn statement_in_program(&mut self, first: bool) -> Result<Option<Statement>, Diagnostic> {
+        // skip empty statements.
+        while matches!(self.peek()?, Token::Semicolon | Token::Newline) {
+            self.next_token()?;
+        }
+
+        // allow trailing separators at the end of the program.
+        if !first && matches!(self.peek()?, Token::Eof) {
+            return Ok(None);
+        }
+
+        self.statement().map(Some)
+    }
+
+    /// Parses a single statement.
+    /// Definitions are parsed as an expression first, if it is followed by a '='
+    /// the expression is reinterpreted as the head of a function definition
+    /// or as the name of an assigned variable.
+    /// # Returns
+    /// * Statement - the parsed statement.
+    fn statement(&mut self) -> Result<Statement, Diagnostic> {
+        let mut head = self.expr(0)?;
+
+        if !matches!(self.peek()?, Token::Equal) {
+            return Ok(Statement::Expression(head));
+        }
+
+        // consume '='.
+        self.next_token()?;
+
+        // the head is taken apart in place, the tree takes care of dropping itself.
+        let (name, arguments) = match &mut head {
+            AST::Call(name, arguments) => (mem::take(name), mem::take(arguments)),
+            AST::Var(name) => return Ok(Statement::Assignment(mem::take(name), self.expr(0)?)),
+            _ => {
+                return Err(self.error("Expected variable name or function definition before '='."))
             }
         };
 
+        // every argument of the head must be a plain name.
+        let parameters = self.parameters(arguments, &format!("definition of '{name}'"))?;
+
+        let body = self.expr(0)?;
+        Ok(Statement::Function(name, parameters, Rc::new(body)))
+    }
+
+    /// Parses an expression, keeping track of how deeply the parser recursed.
+    /// # Arguments
+    /// * min_binding_power - minimum binding power till recursivel parse the expression.
+    /// # Returns
+    /// * AST - ast of the expression.
+    fn expr(&mut self, min_binding_power: u8) -> Result<AST, Diagnostic> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error(format!(
+                "Expression is nested too deeply, maximum depth is {}.",
+                self.options.max_depth
+            )));
+        }
+
+        self.depth += 1;
+        let result = self.pratt_expr(min_binding_power);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Parses an expression using Operator-Precedence parse (Pratt Parsing)
+    /// ref : https://en.wikipedia.org/wiki/Operator-precedence_parser
+    /// # Arguments
+    /// * min_binding_power - minimum binding power till recursivel parse the expression.
+    /// # Returns
+    /// * AST - ast of the expression.
+    fn pratt_expr(&mut self, min_binding_power: u8) -> Result<AST, Diagnostic> {
+        // Parsing left hand side of the expression.
+        let mut left_hand_side = self.prefix()?;
+
         loop {
             // Operator: Infix operator.
-            let operator = match self.lexer.peek() {
+            let operator = match self.peek()? {
+                // a number after a prefix is where it ends.
+                Token::Number(_) if self.prefix => break,
+
                 // shouldn't be a number, obviously.
-                Token::Number(n) => return Err(format!("Expected operator recieved number : {n}")),
+                Token::Number(n) => {
+                    return Err(self.error(format!("Expected operator recieved number : {n}")))
+                }
 
                 // also shouldn't end.
                 Token::Eof => break,
@@ -91,22 +367,24 @@ impl<'a> Parser<'a> {
             };
 
             // get the left binding power of the postfix operator.
-            if let Some((left_bp, ())) = Parser::postfix_binding_power(operator) {
+            if let Some(op) = PostfixOperator::from_token(&operator) {
+                let (left_bp, ()) = op.binding_power();
                 // we break the loop when precendence of the current left binding
                 // power of the postfix operator is less than minimum binding power.
                 if left_bp < min_binding_power {
                     break;
                 }
-                self.lexer.next_token();
+                self.next_token()?;
 
-                left_hand_side = AST::Con(operator, vec![left_hand_side]);
+                left_hand_side = AST::postfix(op, left_hand_side);
 
                 // we need to skip the current iteration.
                 continue;
             }
 
             // get the left binding power and right binding power of this infix operator.
-            if let Some((left_bp, right_bp)) = Parser::infix_binding_power(operator) {
+            if let Some(op) = BinaryOperator::from_token(&operator) {
+                let (left_bp, right_bp) = op.binding_power();
                 // ends recursion when the minimum binding power for this
                 // expr function call is less then left binding power of the current operator.
                 if left_bp < min_binding_power {
@@ -114,17 +392,29 @@ impl<'a> Parser<'a> {
                 }
 
                 // consume operator token.
-                self.lexer.next_token();
+                self.next_token()?;
 
                 // recurisvely call expr to parse right hand side of the expression.
-                match self.expr(right_bp) {
-                    Ok(right_hand_side) => {
-                        // create ast.
-                        left_hand_side = AST::Con(operator, vec![left_hand_side, right_hand_side]);
-                    }
-                    Err(err) => return Err(err),
+                let right_hand_side = self.expr(right_bp)?;
+
+                // create ast.
+                left_hand_side = AST::binary(op, left_hand_side, right_hand_side);
+
+                continue;
+            }
+
+            // an operand right after another one is an implicit multiplication,
+            // when it is enabled.
+            if let Some((left_bp, right_bp)) = self.implicit_binding_power(&operator) {
+                if left_bp < min_binding_power {
+                    break;
                 }
 
+                // no token to consume, the operand is the start of right hand side.
+                let right_hand_side = self.expr(right_bp)?;
+                left_hand_side =
+                    AST::binary(BinaryOperator::Multiply, left_hand_side, right_hand_side);
+
                 continue;
             }
 
@@ -136,52 +426,197 @@ impl<'a> Parser<'a> {
         Ok(left_hand_side)
     }
 
-    /// Gets the infix binding power of a operator.
+    /// Parses the start of an expression, anything before the first infix or postfix operator.
+    /// Each kind of expression is parsed by its own function, keeping the
+    /// stack used by every level of a nested expression small.
+    /// # Returns
+    /// * AST - ast of the expression.
+    fn prefix(&mut self) -> Result<AST, Diagnostic> {
+        match self.next_token()? {
+            // if the token is a number we simply create a node out of it.
+            Token::Number(f) => Ok(AST::Literal(f)),
+
+            // either a variable, a function call or a lambda with single parameter.
+            Token::Identifier(name) => self.identifier(name),
+
+            // let bindings, `let name = value in body`.
+            Token::Let => self.let_binding(),
+
+            // the input ended where an operand was expected, an error pointing at its end.
+            Token::Eof => Err(self.error("Unexpected token : EOF")),
+
+            // if grouping, the AST can be treated as primary expression,
+            // unless it is the parameter list of a lambda.
+            Token::LeftParen => self.group(),
+
+            // list literals.
+            Token::LeftBracket => Ok(AST::List(self.items(Token::RightBracket)?)),
+
+            // if its a operator, then it means the operator is a unary.
+            operator => self.unary(operator),
+        }
+    }
+
+    /// Parses an expression starting with a name, the name must be already consumed.
     /// # Arguments
-    /// * token - the operator token.
-    /// # Returns
-    /// * (left, right) - left and right infix binding power of the operator.
-    fn infix_binding_power(token: Token) -> Option<(u8, u8)> {
-        let power = match token {
-            Token::Plus => (1, 2),
-            Token::Minus => (1, 2),
-            Token::Star => (3, 4),
-            Token::Slash => (3, 4),
-
-            // basically unreachable.
-            _ => return None,
+    /// * name - the name.
+    /// # Returns
+    /// * AST - a variable, a function call or a lambda with single parameter.
+    fn identifier(&mut self, name: &str) -> Result<AST, Diagnostic> {
+        match self.peek()? {
+            Token::LeftParen => {
+                self.next_token()?;
+                Ok(AST::Call(name.to_string(), self.items(Token::RightParen)?))
+            }
+            Token::FatArrow => {
+                self.next_token()?;
+                Ok(AST::Lambda(vec![name.to_string()], Rc::new(self.expr(0)?)))
+            }
+            _ => Ok(AST::Var(name.to_string())),
+        }
+    }
+
+    /// Parses a let binding, `let name = value in body`, 'let' must be already consumed.
+    /// # Returns
+    /// * AST - ast of the binding.
+    fn let_binding(&mut self) -> Result<AST, Diagnostic> {
+        let name = match self.next_token()? {
+            Token::Identifier(name) => name.to_string(),
+            _ => return Err(self.error("Expected name after 'let'.")),
         };
 
-        Some(power)
+        if !matches!(self.next_token()?, Token::Equal) {
+            return Err(self.error(format!("Expected '=' after 'let {name}'.")));
+        }
+
+        let value = self.expr(0)?;
+
+        if !matches!(self.next_token()?, Token::In) {
+            return Err(self.error(format!("Expected 'in' after value of '{name}'.")));
+        }
+
+        let body = self.expr(0)?;
+        Ok(AST::Let(name, Box::new(value), Box::new(body)))
     }
 
-    /// Gets the postfix binding power of a operator.
-    /// # Arguments
-    /// * token - the operator token.
+    /// Parses a grouping or the parameter list and body of a lambda, '(' must be already consumed.
     /// # Returns
-    /// * (left, ())) - left postfix binding power of the operator.
-    fn postfix_binding_power(token: Token) -> Option<(u8, ())> {
-        let power = match token {
-            Token::Bang => (6, ()),
+    /// * AST - ast of the expression.
+    fn group(&mut self) -> Result<AST, Diagnostic> {
+        let items = self.items(Token::RightParen)?;
+
+        if matches!(self.peek()?, Token::FatArrow) {
+            self.next_token()?;
+            let parameters = self.parameters(items, "anonymous function")?;
+            return Ok(AST::Lambda(parameters, Rc::new(self.expr(0)?)));
+        }
 
-            // basically unreachable.
-            _ => return None,
+        let mut items = items.into_iter();
+        match (items.next(), items.next()) {
+            (Some(lhs), None) => Ok(lhs),
+            (None, _) => Err(self.error("Expected expression inside '()'.")),
+            (Some(_), Some(_)) => Err(self.error("Expected '=>' after parameter list.")),
+        }
+    }
+
+    /// Parses a unary expression, the operator must be already consumed.
+    /// # Arguments
+    /// * operator - the unary operator.
+    /// # Returns
+    /// * AST - ast of the expression.
+    fn unary(&mut self, operator: Token<'a>) -> Result<AST, Diagnostic> {
+        // anything but a unary operator can't start an expression.
+        let Some(op) = UnaryOperator::from_token(&operator) else {
+            return Err(self.error(format!(
+                "Unexpected token : {}",
+                operator.to_string().trim()
+            )));
         };
 
-        Some(power)
+        // we get the right binding power of the unary operator.
+        let ((), right_binding_power) = op.binding_power();
+
+        // then recursively parse it.
+        let right_hand_side = self.expr(right_binding_power)?;
+        Ok(AST::unary(op, right_hand_side))
+    }
+
+    /// Parses a comma separated list of expressions, the opening token must be already consumed.
+    /// # Arguments
+    /// * closing - token which ends the list.
+    /// # Returns
+    /// * `Vec<AST>` - ast of each expression.
+    fn items(&mut self, closing: Token<'a>) -> Result<Vec<AST>, Diagnostic> {
+        let mut items = vec![];
+
+        if self.peek()? == closing {
+            self.next_token()?;
+            return Ok(items);
+        }
+
+        loop {
+            items.push(self.expr(0)?);
+
+            match self.next_token()? {
+                Token::Comma => continue,
+                token if token == closing => break,
+                _ => {
+                    return Err(self.error(format!(
+                        "Expected ',' or '{}' after expression.",
+                        closing.to_string().trim()
+                    )))
+                }
+            }
+        }
+
+        Ok(items)
     }
 
-    /// Gets the prefix binding power of a unary operators.
+    /// Converts expressions into parameter names.
     /// # Arguments
-    /// * token - the operator token.
+    /// * items - ast of each parameter, must be plain names.
+    /// * context - what the parameters belong to, used in errors.
     /// # Returns
-    /// * ((), right) - right prefix binding power of the operator.
-    fn prefix_binding_power(token: Token) -> ((), u8) {
-        match token {
-            Token::Minus | Token::Plus => ((), 5),
+    /// * `Vec<String>` - names of the parameters.
+    fn parameters(&self, items: Vec<AST>, context: &str) -> Result<Vec<String>, Diagnostic> {
+        let mut parameters: Vec<String> = vec![];
+        for mut item in items {
+            match &mut item {
+                AST::Var(parameter) => {
+                    let parameter = mem::take(parameter);
+                    if parameters.contains(&parameter) {
+                        return Err(
+                            self.error(format!("Duplicate parameter '{parameter}' in {context}."))
+                        );
+                    }
+                    parameters.push(parameter);
+                }
+                _ => return Err(self.error(format!("Expected parameter names in {context}."))),
+            }
+        }
+
+        Ok(parameters)
+    }
 
-            // basically unreachable.
-            t => panic!("Cannot get infix binding power of {t}"),
+    /// Gets the binding power of an implicit multiplication.
+    /// # Arguments
+    /// * token - the token following an operand.
+    /// # Returns
+    /// * (left, right) - left and right binding power of the implicit multiplication,
+    ///   if it is enabled and the token can start an operand.
+    fn implicit_binding_power(&self, token: &Token) -> Option<(u8, u8)> {
+        // numbers are left out on purpose, `2 3` is much more likely a typo.
+        if !matches!(token, Token::Identifier(_) | Token::LeftParen) {
+            return None;
+        }
+
+        match self.options.implicit_multiplication {
+            ImplicitMultiplication::Disabled => None,
+            ImplicitMultiplication::SameAsExplicit => {
+                Some(BinaryOperator::Multiply.binding_power())
+            }
+            // tighter than '/' and unary minus, but looser than '^' and '!'.
+            ImplicitMultiplication::Tighter => Some((7, 8)),
         }
     }
 }
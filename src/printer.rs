@@ -0,0 +1,348 @@
+use crate::ast::{BinaryOperator, Statement, UnaryOperator, AST};
+
+/// How much space the printer puts around operators and separators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spacing {
+    // spaces around infix operators and after commas, `f(1, 2) + 3 * x`.
+    #[default]
+    Spaced,
+    // no spaces but the ones keywords need, `f(1,2)+3*x`.
+    Compact,
+}
+
+/// Options to change how the printer lays out expressions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrintOptions {
+    // spacing around operators and separators, spaced by default.
+    pub spacing: Spacing,
+}
+
+/// Prints trees back to conventional infix notation, `2 + 3 * (4 - x)`,
+/// using only the parentheses required by precedence and associativity.
+/// Parsing the printed text gives back the same tree, as long as every literal
+/// is a finite non-negative number like the ones the parser produces, other
+/// literals are printed so they evaluate to the same value.
+/// # Examples
+/// ```
+/// use expr_solver::{parse_expression, printer::{PrintOptions, Printer, Spacing}};
+///
+/// let ast = parse_expression("((2 + 3)) * (4 - (x))").unwrap();
+/// assert_eq!(Printer::new().print(&ast), "(2 + 3) * (4 - x)");
+///
+/// let compact = Printer::with_options(PrintOptions { spacing: Spacing::Compact });
+/// assert_eq!(compact.print(&ast), "(2+3)*(4-x)");
+/// ```
+pub struct Printer {
+    options: PrintOptions,
+}
+
+// what is left to print of an ast.
+enum Piece<'t> {
+    // a node and whether it ends the enclosing expression, nothing but a
+    // closing delimiter or the end of input follows it.
+    Ast(&'t AST, bool),
+    // text printed as it is.
+    Text(&'static str),
+    // an infix operator or '=', spaced by the options.
+    Operator(&'static str),
+    // ',' between items, spaced by the options.
+    Separator,
+}
+
+impl Printer {
+    /// Creates a printer with the default options.
+    pub fn new() -> Self {
+        Self::with_options(PrintOptions::default())
+    }
+
+    /// Creates a printer with the given options.
+    /// # Arguments
+    /// * options - how to lay out expressions.
+    pub fn with_options(options: PrintOptions) -> Self {
+        Self { options }
+    }
+
+    /// Prints an expression in infix notation.
+    /// # Arguments
+    /// * ast - the expression.
+    /// # Returns
+    /// * String - text which parses back to the same expression.
+    pub fn print(&self, ast: &AST) -> String {
+        self.render(String::new(), vec![Piece::Ast(ast, true)])
+    }
+
+    /// Prints a statement in infix notation.
+    /// # Arguments
+    /// * statement - the statement.
+    /// # Returns
+    /// * String - text which parses back to the same statement.
+    pub fn print_statement(&self, statement: &Statement) -> String {
+        match statement {
+            Statement::Function(name, parameters, body) => {
+                let head = format!("{name}({})", parameters.join(self.separator()));
+                self.render(head, vec![Piece::Ast(body, true), Piece::Operator("=")])
+            }
+            Statement::Assignment(name, value) => self.render(
+                name.clone(),
+                vec![Piece::Ast(value, true), Piece::Operator("=")],
+            ),
+            Statement::Expression(ast) => self.print(ast),
+        }
+    }
+
+    /// Prints pieces after the given text.
+    /// # Arguments
+    /// * out - text printed so far.
+    /// * pending - pieces left to print, in reverse.
+    /// # Returns
+    /// * String - the whole text.
+    fn render<'t>(&self, mut out: String, mut pending: Vec<Piece<'t>>) -> String {
+        while let Some(piece) = pending.pop() {
+            match piece {
+                Piece::Ast(ast, last) => self.node(ast, last, &mut out, &mut pending),
+                Piece::Text(text) => out.push_str(text),
+                Piece::Operator(symbol) => self.operator(symbol, &mut out),
+                Piece::Separator => out.push_str(self.separator()),
+            }
+        }
+
+        out
+    }
+
+    /// Prints the start of a node and pushes the rest of it, children are
+    /// pushed in reverse so they are printed in order.
+    /// # Arguments
+    /// * ast - the node.
+    /// * last - whether the node ends the enclosing expression.
+    /// * out - text printed so far.
+    /// * pending - pieces left to print.
+    fn node<'t>(&self, ast: &'t AST, last: bool, out: &mut String, pending: &mut Vec<Piece<'t>>) {
+        match ast {
+            AST::Literal(n) => out.push_str(&number(*n)),
+            AST::Var(name) => out.push_str(name),
+            AST::Unary { op, operand } => {
+                let ((), right_bp) = op.binding_power();
+                out.push_str(op.symbol());
+                push_operand(
+                    pending,
+                    operand,
+                    right_parens(operand, right_bp, last),
+                    last,
+                );
+            }
+            AST::Postfix { op, operand } => {
+                let (left_bp, ()) = op.binding_power();
+                pending.push(Piece::Text(op.symbol()));
+                push_operand(pending, operand, left_parens(operand, left_bp), false);
+            }
+            AST::Binary { op, lhs, rhs } => {
+                let (left_bp, right_bp) = op.binding_power();
+                push_operand(pending, rhs, right_parens(rhs, right_bp, last), last);
+                pending.push(Piece::Operator(op.symbol()));
+                push_operand(pending, lhs, left_parens(lhs, left_bp), false);
+            }
+            AST::Call(name, arguments) => {
+                out.push_str(name);
+                push_items(pending, "(", arguments, ")");
+            }
+            AST::List(items) => push_items(pending, "[", items, "]"),
+            AST::Let(name, value, body) => {
+                out.push_str("let ");
+                out.push_str(name);
+                // value ends at 'in', and the body only when the binding does.
+                pending.push(Piece::Ast(body, true));
+                pending.push(Piece::Text(" in "));
+                pending.push(Piece::Ast(value, true));
+                pending.push(Piece::Operator("="));
+            }
+            AST::Lambda(parameters, body) => {
+                match parameters.as_slice() {
+                    [parameter] => out.push_str(parameter),
+                    _ => {
+                        out.push('(');
+                        out.push_str(&parameters.join(self.separator()));
+                        out.push(')');
+                    }
+                }
+                pending.push(Piece::Ast(body, true));
+                pending.push(Piece::Operator("=>"));
+            }
+        }
+    }
+
+    /// Prints an infix operator.
+    /// # Arguments
+    /// * symbol - the operator.
+    /// * out - text printed so far.
+    fn operator(&self, symbol: &str, out: &mut String) {
+        match self.options.spacing {
+            Spacing::Spaced => {
+                out.push(' ');
+                out.push_str(symbol);
+                out.push(' ');
+            }
+            Spacing::Compact => {
+                // `3!==3` would be read as `3 != = 3`.
+                if out.ends_with('!') && symbol.starts_with('=') {
+                    out.push(' ');
+                }
+                out.push_str(symbol);
+            }
+        }
+    }
+
+    /// Returns the text between items of a list.
+    fn separator(&self) -> &'static str {
+        match self.options.spacing {
+            Spacing::Spaced => ", ",
+            Spacing::Compact => ",",
+        }
+    }
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pushes an operand, wrapped in parentheses if needed.
+/// # Arguments
+/// * pending - pieces left to print.
+/// * operand - the operand.
+/// * parens - whether the operand needs parentheses.
+/// * last - whether the operand ends the enclosing expression.
+fn push_operand<'t>(pending: &mut Vec<Piece<'t>>, operand: &'t AST, parens: bool, last: bool) {
+    if parens {
+        pending.push(Piece::Text(")"));
+        pending.push(Piece::Ast(operand, true));
+        pending.push(Piece::Text("("));
+    } else {
+        pending.push(Piece::Ast(operand, last));
+    }
+}
+
+/// Pushes items between delimiters, separated by commas.
+/// # Arguments
+/// * pending - pieces left to print.
+/// * open - the opening delimiter.
+/// * items - the items.
+/// * close - the closing delimiter.
+fn push_items<'t>(
+    pending: &mut Vec<Piece<'t>>,
+    open: &'static str,
+    items: &'t [AST],
+    close: &'static str,
+) {
+    pending.push(Piece::Text(close));
+    for (i, item) in items.iter().enumerate().rev() {
+        pending.push(Piece::Ast(item, true));
+        if i > 0 {
+            pending.push(Piece::Separator);
+        }
+    }
+    pending.push(Piece::Text(open));
+}
+
+/// Checks whether an operand on the left of an operator needs parentheses,
+/// the operand must end before the operator is read.
+/// # Arguments
+/// * operand - the operand.
+/// * left_bp - left binding power of the operator.
+fn left_parens(operand: &AST, left_bp: u8) -> bool {
+    match operand {
+        AST::Binary { op, .. } => op.binding_power().1 <= left_bp,
+        AST::Unary { op, .. } => op.binding_power().1 <= left_bp,
+        // printed as a unary minus.
+        AST::Literal(n) if n.is_sign_negative() => 7 <= left_bp,
+        // their bodies take everything after them.
+        AST::Let(..) | AST::Lambda(..) => true,
+        _ => false,
+    }
+}
+
+/// Checks whether an operand on the right of an operator needs parentheses,
+/// the whole operand must be read before the operator ends.
+/// # Arguments
+/// * operand - the operand.
+/// * right_bp - right binding power of the operator.
+/// * last - whether the operand ends the enclosing expression.
+fn right_parens(operand: &AST, right_bp: u8, last: bool) -> bool {
+    match operand {
+        AST::Binary { op, .. } => op.binding_power().0 < right_bp,
+        // their bodies take everything after them, which is fine if nothing follows.
+        AST::Let(..) | AST::Lambda(..) => !last,
+        _ => false,
+    }
+}
+
+/// Where an operand sits in typeset notation, LaTeX and MathML.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Operand {
+    // on the left of an infix operator with the given left binding power.
+    Left(u8),
+    // on the right of an operator with the given right binding power.
+    Right(u8),
+    // the base of a power or the operand of a factorial, anything but a
+    // single symbol is grouped.
+    Base,
+}
+
+/// Checks whether an operand needs parentheses in typeset notation,
+/// where fractions and roots group their contents without them.
+/// # Arguments
+/// * operand - the operand.
+/// * position - where the operand sits.
+pub(crate) fn typeset_parens(operand: &AST, position: Operand) -> bool {
+    let negative = match operand {
+        AST::Unary { op, .. } => *op != UnaryOperator::Root,
+        AST::Literal(n) => n.is_sign_negative(),
+        _ => false,
+    };
+
+    // numbers in scientific notation are a product themselves.
+    let symbol = match operand {
+        AST::Literal(n) => !negative && !scientific(*n),
+        AST::Var(_) | AST::Call(..) | AST::List(_) => true,
+        _ => false,
+    };
+
+    match (operand, position) {
+        _ if symbol => false,
+        (_, Operand::Base) => true,
+        (AST::Let(..) | AST::Lambda(..), _) => true,
+        (AST::Binary { op, .. }, _) if *op == BinaryOperator::Divide => false,
+        (AST::Unary { op, .. }, _) if *op == UnaryOperator::Root => false,
+        // `a - (-b)` reads better than `a - -b`.
+        (_, Operand::Right(_)) if negative => true,
+        (_, Operand::Left(left_bp)) => left_parens(operand, left_bp),
+        (_, Operand::Right(right_bp)) => right_parens(operand, right_bp, false),
+    }
+}
+
+/// Prints a number so the lexer reads back the same value.
+/// # Arguments
+/// * n - the number.
+/// # Returns
+/// * String - the number, in scientific notation if it is very large or very small.
+fn number(n: f64) -> String {
+    if n.is_nan() {
+        return "(0 / 0)".to_string();
+    }
+    if n.is_infinite() {
+        // too large for a float, the lexer reads it as infinity.
+        return if n > 0.0 { "1e999" } else { "-1e999" }.to_string();
+    }
+
+    if scientific(n) {
+        format!("{n:e}")
+    } else {
+        format!("{n}")
+    }
+}
+
+/// Checks whether a number is printed in scientific notation, it is very large or very small.
+pub(crate) fn scientific(n: f64) -> bool {
+    let magnitude = n.abs();
+    n.is_finite() && magnitude != 0.0 && !(1e-5..1e16).contains(&magnitude)
+}
@@ -0,0 +1,61 @@
+//! Serialization with serde, enabled by the `serde` feature.
+//!
+//! Tokens, spans, errors, statements and values derive `Serialize` and
+//! `Deserialize`, with enum variants in `snake_case` and externally tagged,
+//! serde's default.
+//!
+//! | Type                     | JSON                                                      |
+//! |--------------------------|-----------------------------------------------------------|
+//! | `Token`                  | `"plus"`, `{"number": 2.0}`, `{"identifier": "x"}`        |
+//! | `Span`                   | `{"start": 0, "end": 1}`                                  |
+//! | `SpannedToken`           | `{"token": "plus", "span": {"start": 0, "end": 1}}`       |
+//! | `Diagnostic`, `LexError` | `{"message": "...", "span": {"start": 0, "end": 1}}`      |
+//! | operators                | `"add"`, `"less_equal"`, `"negate"`, `"factorial"`        |
+//! | `Statement`              | `{"function": ["f", ["x"], AST]}`, `{"assignment": ["x", AST]}`, `{"expression": AST}` |
+//! | `Value`                  | `{"number": 2.0}`, `{"list": [Value]}`, `{"function": {"parameters": ["x"], "body": AST, "captured": {"y": Value}}}` |
+//! | `EvalError`              | `{"message": "..."}`, `{"limit": {"call_depth": 256}}`, `{"limit": "cancelled"}` |
+//!
+//! So the `Result<Value, EvalError>` of an evaluation is `{"Ok": Value}` or `{"Err": EvalError}`.
+//!
+//! An `AST` is a flat list of instructions in postfix order, children before
+//! their parent, the way a stack machine would build the tree, so `2 * x + 1` is
+//!
+//! ```json
+//! [{"literal": 2.0}, {"var": "x"}, {"binary": "multiply"}, {"literal": 1.0}, {"binary": "add"}]
+//! ```
+//!
+//! | Instruction                               | Builds                                              |
+//! |-------------------------------------------|-----------------------------------------------------|
+//! | `{"literal": 2.0}`                        | a number                                            |
+//! | `{"var": "x"}`                            | a variable                                          |
+//! | `{"unary": "negate"}`                     | a unary expression of the last node                 |
+//! | `{"postfix": "factorial"}`                | a postfix expression of the last node               |
+//! | `{"binary": "add"}`                       | a binary expression of the last two nodes           |
+//! | `{"call": {"name": "f", "arguments": 2}}` | a call with the last `arguments` nodes              |
+//! | `{"let": "x"}`                            | a let binding, the last two nodes are value and body |
+//! | `{"lambda": ["x", "y"]}`                  | an anonymous function with the last node as body    |
+//! | `{"list": 3}`                             | a list of the last `3` nodes                        |
+//!
+//! A flat list keeps trees of any depth within the nesting limits of formats
+//! like JSON, and neither writing nor reading one recurses.
+//! JSON can't hold infinite numbers or NaN, serde_json writes them as `null`,
+//! and reads the last digit of some numbers back wrong unless its
+//! `float_roundtrip` feature is enabled.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ast::AST;
+use crate::binary::{build, instructions};
+
+impl Serialize for AST {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        instructions(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AST {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        build(Vec::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,374 @@
+use std::rc::Rc;
+
+use crate::ast::{AST, BINARY_OPERATORS, POSTFIX_OPERATORS, UNARY_OPERATORS};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Lexer;
+use crate::token::Span;
+
+/// Reads a tree back from the S-expression form `Display` prints for it,
+/// `( +   2 ( *   3   x ))`, so trees can be stored as text and loaded
+/// without going through infix parsing.
+/// Whitespace only separates atoms, any amount of it is accepted.
+/// `#inf`, `-#inf` and `#nan` are read as numbers, the way `Display` prints them.
+/// # Arguments
+/// * input - the S-expression.
+/// # Returns
+/// * AST - the tree, or a diagnostic pointing at the first problem.
+/// # Examples
+/// ```
+/// use expr_solver::{ast::{BinaryOperator, AST}, sexpr};
+///
+/// let ast = AST::binary(BinaryOperator::Add, AST::Literal(2.0), AST::Var("x".to_string()));
+/// assert_eq!(ast.to_string(), "( +   2   x )");
+/// assert_eq!(sexpr::read(&ast.to_string()), Ok(ast));
+///
+/// assert_eq!(sexpr::read("(+ 2 (* 3 4))").unwrap().to_string(), "( +   2 ( *   3   4 ))");
+/// assert!(sexpr::read("(+ 2").is_err());
+/// ```
+pub fn read(input: &str) -> Result<AST, Diagnostic> {
+    let mut reader = Reader { input, position: 0 };
+
+    // lists still being read, innermost last.
+    let mut open: Vec<Frame> = vec![];
+    let mut result = None;
+
+    loop {
+        let (atom, span) = reader.next_atom();
+        let node = match atom {
+            Atom::Open => {
+                let head = reader.head(span)?;
+                open.push(Frame::new(head, span));
+                continue;
+            }
+            Atom::OpenBracket => {
+                open.push(Frame::new(Head::List, span));
+                continue;
+            }
+            Atom::Close | Atom::CloseBracket => {
+                let Some(frame) = open.pop() else {
+                    return Err(error(format!("Unexpected token : {}", atom.text()), span));
+                };
+                frame.close(atom, span)?
+            }
+            Atom::Text(text) => value(text, span)?,
+            Atom::End => {
+                return match (open.last(), result) {
+                    (Some(frame), _) => Err(error(
+                        format!("Expected '{}' to close this.", frame.closing().text()),
+                        frame.span,
+                    )),
+                    (None, None) => Err(error("Expected expression.", span)),
+                    (None, Some(result)) => Ok(result),
+                }
+            }
+        };
+
+        match open.last_mut() {
+            Some(frame) => frame.children.push(node),
+            None if result.is_none() => result = Some(node),
+            None => {
+                return Err(error(
+                    "Unexpected token after end of expression.",
+                    Span {
+                        start: span.start,
+                        end: input.len(),
+                    },
+                ))
+            }
+        }
+    }
+}
+
+// pieces of an S-expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Atom<'a> {
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    // anything else up to whitespace or a bracket, numbers, names and operators.
+    Text(&'a str),
+    End,
+}
+
+impl Atom<'_> {
+    /// Returns the text of the atom, used in errors.
+    fn text(&self) -> &str {
+        match self {
+            Atom::Open => "(",
+            Atom::Close => ")",
+            Atom::OpenBracket => "[",
+            Atom::CloseBracket => "]",
+            Atom::Text(text) => text,
+            Atom::End => "EOF",
+        }
+    }
+}
+
+// what a list starts with, decides which node it becomes.
+enum Head<'a> {
+    // an operator, unary or binary by the number of operands.
+    Operator(&'a str),
+    Call(&'a str),
+    Let(&'a str),
+    Lambda,
+    List,
+}
+
+// a list still being read.
+struct Frame<'a> {
+    head: Head<'a>,
+    children: Vec<AST>,
+    // where the list starts, errors about the whole list point at it.
+    span: Span,
+}
+
+impl<'a> Frame<'a> {
+    fn new(head: Head<'a>, span: Span) -> Self {
+        Self {
+            head,
+            children: vec![],
+            span,
+        }
+    }
+
+    /// Returns the bracket which closes the list.
+    fn closing(&self) -> Atom<'static> {
+        match self.head {
+            Head::List => Atom::CloseBracket,
+            _ => Atom::Close,
+        }
+    }
+
+    /// Builds the node of a finished list.
+    /// # Arguments
+    /// * atom - the closing bracket.
+    /// * span - where the closing bracket is.
+    /// # Returns
+    /// * AST - the node.
+    fn close(self, atom: Atom, span: Span) -> Result<AST, Diagnostic> {
+        if atom == self.closing() {
+            let span = Span {
+                start: self.span.start,
+                end: span.end,
+            };
+            return self.build(span);
+        }
+
+        Err(error(
+            format!(
+                "Expected '{}' but found '{}'.",
+                self.closing().text(),
+                atom.text()
+            ),
+            span,
+        ))
+    }
+
+    /// Builds the node from the head and the children of the list.
+    /// # Arguments
+    /// * span - the whole list.
+    /// # Returns
+    /// * AST - the node.
+    fn build(mut self, span: Span) -> Result<AST, Diagnostic> {
+        let count = self.children.len();
+        match self.head {
+            Head::Operator(symbol) => operator(symbol, &mut self.children)
+                .ok_or_else(|| error(format!("'{symbol}' doesn't take {count} operands."), span)),
+            Head::Call(name) => Ok(AST::Call(name.to_string(), self.children)),
+            Head::List => Ok(AST::List(self.children)),
+            Head::Let(name) => {
+                let body = self.children.pop();
+                match (self.children.pop(), body) {
+                    (Some(value), Some(body)) if count == 2 => {
+                        Ok(AST::Let(name.to_string(), Box::new(value), Box::new(body)))
+                    }
+                    _ => Err(error(
+                        format!("Expected value and body in 'let {name}', found {count}."),
+                        span,
+                    )),
+                }
+            }
+            Head::Lambda => lambda(self.children, span),
+        }
+    }
+}
+
+/// Builds an operator node, which kind depends on the number of operands.
+/// # Arguments
+/// * symbol - the operator.
+/// * operands - the operands.
+/// # Returns
+/// * AST - the node, None if the operator doesn't take that many operands.
+fn operator(symbol: &str, operands: &mut Vec<AST>) -> Option<AST> {
+    match operands.len() {
+        1 => {
+            let operand = operands.pop()?;
+            if let Some(op) = UNARY_OPERATORS.into_iter().find(|op| op.symbol() == symbol) {
+                return Some(AST::unary(op, operand));
+            }
+            let op = POSTFIX_OPERATORS
+                .into_iter()
+                .find(|op| op.symbol() == symbol)?;
+            Some(AST::postfix(op, operand))
+        }
+        2 => {
+            let op = BINARY_OPERATORS
+                .into_iter()
+                .find(|op| op.symbol() == symbol)?;
+            let rhs = operands.pop()?;
+            Some(AST::binary(op, operands.pop()?, rhs))
+        }
+        _ => None,
+    }
+}
+
+/// Builds a lambda out of its parameter names followed by its body.
+/// # Arguments
+/// * children - the parameters and the body, the last one is the body.
+/// * span - the whole list, errors point at it.
+/// # Returns
+/// * AST - the lambda.
+fn lambda(mut children: Vec<AST>, span: Span) -> Result<AST, Diagnostic> {
+    let Some(body) = children.pop() else {
+        return Err(error("Expected a body in anonymous function.", span));
+    };
+
+    let mut parameters = vec![];
+    for parameter in &children {
+        match parameter {
+            AST::Var(name) => parameters.push(name.clone()),
+            _ => {
+                return Err(error(
+                    "Expected parameter names in anonymous function.",
+                    span,
+                ))
+            }
+        }
+    }
+
+    Ok(AST::Lambda(parameters, Rc::new(body)))
+}
+
+/// Reads a number or a variable.
+/// # Arguments
+/// * text - the atom.
+/// * span - where the atom is.
+/// # Returns
+/// * AST - the literal or the variable.
+fn value(text: &str, span: Span) -> Result<AST, Diagnostic> {
+    match text {
+        "#inf" => return Ok(AST::Literal(f64::INFINITY)),
+        "-#inf" => return Ok(AST::Literal(f64::NEG_INFINITY)),
+        "#nan" => return Ok(AST::Literal(f64::NAN)),
+        _ => {}
+    }
+
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return match text.parse::<f64>() {
+            Ok(n) => Ok(AST::Literal(n)),
+            Err(_) => Err(error(format!("Invalid number literal '{text}'."), span)),
+        };
+    }
+
+    if is_name(text) {
+        return Ok(AST::Var(text.to_string()));
+    }
+
+    Err(error(format!("Unexpected token : {text}"), span))
+}
+
+/// Checks whether an atom can be the name of a variable or a function.
+fn is_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(Lexer::is_identifier_start)
+        && chars.all(Lexer::is_identifier_part)
+        && !matches!(text, "let" | "in")
+}
+
+/// Creates a diagnostic.
+fn error(message: impl Into<String>, span: Span) -> Diagnostic {
+    Diagnostic {
+        message: message.into(),
+        span,
+    }
+}
+
+// splits the input into atoms.
+struct Reader<'a> {
+    input: &'a str,
+    // byte offset of the next character.
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Reads the next atom, skipping whitespace.
+    /// # Returns
+    /// * (Atom, Span) - the atom and where it is.
+    fn next_atom(&mut self) -> (Atom<'a>, Span) {
+        let rest = &self.input[self.position..];
+        let start = self.position + (rest.len() - rest.trim_start().len());
+        let rest = &self.input[start..];
+
+        let (atom, length) = match rest.chars().next() {
+            None => (Atom::End, 0),
+            Some('(') => (Atom::Open, 1),
+            Some(')') => (Atom::Close, 1),
+            Some('[') => (Atom::OpenBracket, 1),
+            Some(']') => (Atom::CloseBracket, 1),
+            Some(_) => {
+                let length = rest
+                    .find(|c: char| c.is_whitespace() || "()[]".contains(c))
+                    .unwrap_or(rest.len());
+                (Atom::Text(&rest[..length]), length)
+            }
+        };
+
+        self.position = start + length;
+        let span = Span {
+            start,
+            end: self.position,
+        };
+        (atom, span)
+    }
+
+    /// Reads what a list starts with, '(' must be already consumed.
+    /// # Arguments
+    /// * open - where the '(' is, errors point at it.
+    /// # Returns
+    /// * Head - kind of the list.
+    fn head(&mut self, open: Span) -> Result<Head<'a>, Diagnostic> {
+        let (atom, span) = self.next_atom();
+        let Atom::Text(text) = atom else {
+            return Err(error(
+                format!(
+                    "Expected operator or name after '(', found '{}'.",
+                    atom.text()
+                ),
+                span,
+            ));
+        };
+
+        let is_operator = BINARY_OPERATORS.iter().any(|op| op.symbol() == text)
+            || UNARY_OPERATORS.iter().any(|op| op.symbol() == text)
+            || POSTFIX_OPERATORS.iter().any(|op| op.symbol() == text);
+
+        match text {
+            "=>" => Ok(Head::Lambda),
+            "let" => match self.next_atom() {
+                (Atom::Text(name), _) if is_name(name) => Ok(Head::Let(name)),
+                (_, span) => Err(error("Expected name after 'let'.", span)),
+            },
+            _ if is_operator => Ok(Head::Operator(text)),
+            _ if is_name(text) => Ok(Head::Call(text)),
+            _ => Err(error(
+                format!("Expected operator or name after '(', found '{text}'."),
+                Span {
+                    start: open.start,
+                    end: span.end,
+                },
+            )),
+        }
+    }
+}
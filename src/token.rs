@@ -1,33 +1,92 @@
 // List of all the tokens possible.
-#[derive(Debug, Clone, Copy)]
-pub enum Token {
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Token<'a> {
     // we store numbers as rust's f64.
     Number(f64),
+    // names of variables and functions, borrowed from the source.
+    Identifier(&'a str),
     // operators.
     Plus,
     Minus,
     Star,
     Slash,
+    Caret,
     Bang,
+    Root,
+    // comparisons.
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    EqualEqual,
+    BangEqual,
     // Grouping
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
+    // definitions and separators.
+    Equal,
+    FatArrow,
+    Comma,
+    Semicolon,
+    Newline,
+    // keywords.
+    Let,
+    In,
     // end of file for ease of use.
     Eof,
 }
 
-impl core::fmt::Display for Token {
+impl core::fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Token::Number(n) => write!(f, " {n} "),
+            Token::Identifier(name) => write!(f, " {name} "),
             Token::Plus => write!(f, " + "),
             Token::Minus => write!(f, " - "),
             Token::Star => write!(f, " * "),
             Token::Slash => write!(f, " / "),
+            Token::Caret => write!(f, " ^ "),
             Token::Bang => write!(f, " ! "),
+            Token::Root => write!(f, " √ "),
+            Token::Less => write!(f, " < "),
+            Token::LessEqual => write!(f, " <= "),
+            Token::Greater => write!(f, " > "),
+            Token::GreaterEqual => write!(f, " >= "),
+            Token::EqualEqual => write!(f, " == "),
+            Token::BangEqual => write!(f, " != "),
             Token::Eof => write!(f, " EOF "),
             Token::LeftParen => write!(f, " ( "),
             Token::RightParen => write!(f, " ) "),
+            Token::LeftBracket => write!(f, " [ "),
+            Token::RightBracket => write!(f, " ] "),
+            Token::Equal => write!(f, " = "),
+            Token::FatArrow => write!(f, " => "),
+            Token::Comma => write!(f, " , "),
+            Token::Semicolon => write!(f, " ; "),
+            Token::Newline => write!(f, " \\n "),
+            Token::Let => write!(f, " let "),
+            Token::In => write!(f, " in "),
         }
     }
 }
+
+/// Byte offsets of a token in the source string, `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A token along with where it was found in the source string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpannedToken<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub token: Token<'a>,
+    pub span: Span,
+}
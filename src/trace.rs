@@ -0,0 +1,162 @@
+use std::rc::Rc;
+
+use crate::ast::{BinaryOperator, PostfixOperator, UnaryOperator, AST};
+use crate::printer::Printer;
+use crate::value::Value;
+use crate::visit::preorder;
+
+/// What a step of an evaluation did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Binary(BinaryOperator),
+    Unary(UnaryOperator),
+    Postfix(PostfixOperator),
+    // calls of functions, builtin or defined, by name.
+    Call(String),
+}
+
+/// A reduction of a sub-expression to its value, recorded by an interpreter
+/// created `with_trace`.
+#[derive(Debug, Clone)]
+pub struct Step {
+    // the evaluated expression, or the body of the called function,
+    // shared by every step of its evaluation.
+    pub tree: Rc<AST>,
+    // position of the reduced sub-expression in the tree, counting its nodes
+    // in preorder from 0.
+    pub node: usize,
+    pub operation: Operation,
+    // values of the operands or arguments, in order.
+    pub operands: Vec<Value>,
+    pub result: Value,
+    // how many function calls deep the step happened,
+    // 0 is the evaluated expression itself.
+    pub depth: usize,
+}
+
+impl Step {
+    /// Returns the reduced sub-expression, found by walking the tree.
+    pub fn expression(&self) -> &AST {
+        preorder(&self.tree).nth(self.node).unwrap_or(&self.tree)
+    }
+}
+
+impl core::fmt::Display for Step {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} = {}",
+            Printer::new().print(self.expression()),
+            self.result
+        )
+    }
+}
+
+/// Renders the steps of an evaluation as the expression rewritten after each one,
+/// every reduced sub-expression replaced with its value.
+/// Steps inside function calls don't rewrite the expression, it only shows the call,
+/// and let bindings and lists have no step of their own, so they stay in the last line.
+/// Each line prints the whole expression again, so long traces of big trees take a while.
+/// # Arguments
+/// * ast - the evaluated expression.
+/// * steps - the steps recorded while evaluating it.
+/// # Returns
+/// * `Vec<String>` - the expression before any step, then after every step.
+/// # Examples
+/// ```
+/// use expr_solver::{interpreter::Interpreter, parse_expression, trace::rewrite};
+///
+/// let ast = parse_expression("2 + 3 * 4").unwrap();
+/// let mut interpreter = Interpreter::new().with_trace();
+/// interpreter.evaluate(&ast).unwrap();
+///
+/// let steps = interpreter.take_trace();
+/// assert_eq!(steps[0].to_string(), "3 * 4 = 12");
+/// assert_eq!(rewrite(&ast, &steps).join(" → "), "2 + 3 * 4 → 2 + 12 → 14");
+/// ```
+pub fn rewrite(ast: &AST, steps: &[Step]) -> Vec<String> {
+    let printer = Printer::new();
+    let sizes = sizes(ast);
+
+    let mut values = vec![None; sizes.len()];
+    let mut lines = vec![printer.print(ast)];
+
+    for step in steps.iter().filter(|step| step.depth == 0) {
+        let Some(value) = values.get_mut(step.node) else {
+            continue;
+        };
+        *value = Some(&step.result);
+
+        let mut rewritten = ast.clone();
+        substitute(&mut rewritten, &values, &sizes);
+
+        // negating a literal reads the same before and after.
+        let line = printer.print(&rewritten);
+        if lines.last() != Some(&line) {
+            lines.push(line);
+        }
+    }
+
+    lines
+}
+
+/// Counts the nodes of the sub-tree at every position of a tree.
+/// # Arguments
+/// * ast - the tree.
+/// # Returns
+/// * `Vec<usize>` - the sizes, in preorder.
+fn sizes(ast: &AST) -> Vec<usize> {
+    let nodes: Vec<&AST> = preorder(ast).collect();
+    let mut sizes = vec![1; nodes.len()];
+
+    // children come right after their parent, one sub-tree after the other,
+    // so going backwards they are counted before it.
+    for (i, node) in nodes.iter().enumerate().rev() {
+        let children = match node {
+            AST::Literal(_) | AST::Var(_) => 0,
+            AST::Unary { .. } | AST::Postfix { .. } | AST::Lambda(..) => 1,
+            AST::Binary { .. } | AST::Let(..) => 2,
+            AST::Call(_, children) | AST::List(children) => children.len(),
+        };
+
+        let mut child = i + 1;
+        for _ in 0..children {
+            sizes[i] += sizes[child];
+            child += sizes[child];
+        }
+    }
+
+    sizes
+}
+
+/// Replaces the sub-trees at the given positions with their values,
+/// numbers become literals, lists and functions are shown the way they print.
+/// # Arguments
+/// * ast - the tree, rewritten in place.
+/// * values - value of the sub-tree at every position, if it was reduced.
+/// * sizes - size of the sub-tree at every position.
+fn substitute(ast: &mut AST, values: &[Option<&Value>], sizes: &[usize]) {
+    let mut pending = vec![(ast, 0)];
+
+    while let Some((ast, position)) = pending.pop() {
+        match values[position] {
+            Some(Value::Number(n)) => *ast = AST::Literal(*n),
+            Some(value) => *ast = AST::Var(value.to_string()),
+            None => {}
+        }
+
+        // bodies of anonymous functions are never reduced.
+        let children: Vec<&mut AST> = match ast {
+            AST::Unary { operand, .. } | AST::Postfix { operand, .. } => vec![operand],
+            AST::Binary { lhs, rhs, .. } | AST::Let(_, lhs, rhs) => vec![lhs, rhs],
+            AST::Call(_, children) | AST::List(children) => children.iter_mut().collect(),
+            _ => vec![],
+        };
+
+        let mut child = position + 1;
+        for ast in children {
+            pending.push((ast, child));
+            child += sizes[child];
+        }
+    }
+}
@@ -1,67 +1,136 @@
-const TAYLOR_COEFFICIENTS: [f64; 29] = [
-    -0.00000000000000000023,
-    0.00000000000000000141,
-    0.00000000000000000119,
-    -0.00000000000000011813,
-    0.00000000000000122678,
-    -0.00000000000000534812,
-    -0.00000000000002058326,
-    0.00000000000051003703,
-    -0.00000000000369680562,
-    0.00000000000778226344,
-    0.00000000010434267117,
-    -0.00000000118127457049,
-    0.00000000500200764447,
-    0.00000000611609510448,
-    -0.00000020563384169776,
-    0.00000113302723198170,
-    -0.00000125049348214267,
-    -0.00002013485478078824,
-    0.00012805028238811619,
-    -0.00021524167411495097,
-    -0.00116516759185906511,
-    0.00721894324666309954,
-    -0.00962197152787697356,
-    -0.04219773455554433675,
-    0.16653861138229148950,
-    -0.04200263503409523553,
-    -0.65587807152025388108,
-    0.57721566490153286061,
-    1.00000000000000000000,
+use std::f64::consts::PI;
+
+// coefficients of the Lanczos approximation with g = 7 and 9 terms,
+// accurate to about 15 significant digits.
+// https://en.wikipedia.org/wiki/Lanczos_approximation
+const LANCZOS_G: f64 = 7.0;
+
+#[allow(clippy::excessive_precision)]
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.99999999999980993,
+    676.5203681218851,
+    -1259.1392167224028,
+    771.32342877765313,
+    -176.61502916214059,
+    12.507343278686905,
+    -0.13857109526572012,
+    9.9843695780195716e-6,
+    1.5056327351493116e-7,
 ];
 
-const INITIAL_SUM: f64 = 0.00000000000000000002;
+// gamma of anything above this doesn't fit in a f64.
+const MAX_GAMMA_ARGUMENT: f64 = 171.7;
+
+/// Calculates gamma of a f64, `gamma(n) = (n - 1)!`, with the Lanczos
+/// approximation, and the reflection formula below 1/2.
+/// <https://en.wikipedia.org/wiki/Gamma_function>
+/// # Arguments
+/// * x - the number.
+/// # Returns
+/// * f64 - gamma of x, infinite at 0 and above 171.7 where it overflows,
+///   NaN at negative integers where gamma has its poles.
+/// # Examples
+/// ```
+/// use expr_solver::utils::gamma;
+///
+/// assert!((gamma(0.5) - std::f64::consts::PI.sqrt()).abs() < 1e-15);
+/// assert!((gamma(-1.5) - 2.3632718012073548).abs() < 1e-14);
+/// ```
+pub fn gamma(x: f64) -> f64 {
+    if x == 0.0 {
+        // signed like 1 / x.
+        return x.recip();
+    }
+    if x < 0.0 && x.fract() == 0.0 {
+        return f64::NAN;
+    }
+    if x > MAX_GAMMA_ARGUMENT {
+        return f64::INFINITY;
+    }
+
+    // gamma(x) * gamma(1 - x) = pi / sin(pi * x).
+    if x < 0.5 {
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
 
-// Calculates gamma of a f64.
-// https://en.wikipedia.org/wiki/Gamma_function
-fn gamma(x: f64) -> f64 {
-    TAYLOR_COEFFICIENTS
+    let (t, series) = lanczos(x);
+    // the power is split in two, it overflows on its own before gamma does.
+    let power = t.powf((x - 0.5) / 2.0);
+    (2.0 * PI).sqrt() * power * (-t).exp() * power * series
+}
+
+/// Calculates the natural logarithm of the absolute value of gamma of a f64,
+/// which stays finite long after gamma overflows, so huge factorials can be
+/// compared and combined in log space, `lgamma(n + 1) = ln(n!)`.
+/// # Arguments
+/// * x - the number.
+/// # Returns
+/// * f64 - ln |gamma(x)|, infinite at 0 and negative integers where gamma has its poles.
+/// # Examples
+/// ```
+/// use expr_solver::utils::lgamma;
+///
+/// // ln(1000!)
+/// assert!((lgamma(1001.0) - 5912.128178488163).abs() < 1e-9);
+/// ```
+pub fn lgamma(x: f64) -> f64 {
+    if x.is_infinite() || (x <= 0.0 && x.fract() == 0.0) {
+        return f64::INFINITY;
+    }
+
+    // ln |gamma(x)| + ln |gamma(1 - x)| = ln |pi / sin(pi * x)|.
+    if x < 0.5 {
+        return (PI / (PI * x).sin().abs()).ln() - lgamma(1.0 - x);
+    }
+
+    let (t, series) = lanczos(x);
+    0.5 * (2.0 * PI).ln() + (x - 0.5) * t.ln() - t + series.ln()
+}
+
+// Calculates the parts of the Lanczos approximation of gamma(x) for x >= 1/2,
+// gamma(x) = sqrt(2 * pi) * t^(x - 1/2) * e^-t * series.
+fn lanczos(x: f64) -> (f64, f64) {
+    let x = x - 1.0;
+    let series = LANCZOS_COEFFICIENTS[1..]
         .iter()
-        .fold(INITIAL_SUM, |sum, coefficient| {
-            sum * (x - 1.0) + coefficient
-        })
-        .recip()
+        .enumerate()
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (i, coefficient)| {
+            sum + coefficient / (x + i as f64 + 1.0)
+        });
+    (x + LANCZOS_G + 0.5, series)
 }
 
 // Calculates simple factorial of a f64.
+// anything above 170! doesn't fit in a f64, so we stop multiplying there.
 fn simple_factorial(x: f64) -> f64 {
-    if x <= 1.0 {
-        return 1.0;
+    if x > 170.0 {
+        return f64::INFINITY;
     }
 
-    simple_factorial(x - 1.0) * x
+    let mut result = 1.0;
+    let mut n = 2.0;
+    while n <= x {
+        result *= n;
+        n += 1.0;
+    }
+    result
 }
 
 /// Wrapper function to calculate factorial of a function.
 /// Returns simple factorial of x if the number can be converted to integer without loss.
-/// Returns gamma of x if the number cannot be converted to integer without loss.
+/// Returns gamma of x + 1 if the number cannot be converted to integer without loss,
+/// negative numbers included.
+/// Returns NaN for negative integers, where gamma has its poles.
 /// # Arguments
 /// * x - the number to find factorial of.
 pub fn factorial(x: f64) -> f64 {
     log::trace!("getting factorial of {}", x);
 
-    // for integers.
+    // for integers, negative ones have no factorial.
     if x.fract() == 0.0 {
+        if x < 0.0 {
+            return f64::NAN;
+        }
         return simple_factorial(x);
     }
 
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::AST;
+
+/// A function value, either defined by name, `f(x, y) = x * y`,
+/// or anonymous, `(x, y) => x * y`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Function {
+    // names of the parameters.
+    pub parameters: Vec<String>,
+    // expression evaluated when the function is called.
+    pub body: Rc<AST>,
+    // local variables visible where the function was created.
+    pub captured: HashMap<String, Value>,
+}
+
+/// Enum for modeling values an expression can evaluate to.
+/// Lists and functions capturing other values can nest as deeply as the
+/// program builds them, so dropping and printing values never recurse.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Value {
+    // plain numbers.
+    Number(f64),
+    // lists of values, `[1, 2, 3]`.
+    List(Rc<Vec<Value>>),
+    // first class functions.
+    Function(Rc<Function>),
+}
+
+impl Value {
+    /// Returns the inner number of the value.
+    /// # Returns
+    /// Result with the number, otherwise error string if the value is not a number.
+    pub fn as_number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::List(_) => Err("Expected a number, found a list.".to_string()),
+            Value::Function(_) => Err("Expected a number, found a function.".to_string()),
+        }
+    }
+
+    /// Moves the values held by this value to the stack, unless they are shared.
+    /// # Arguments
+    /// * stack - values waiting to be dropped.
+    fn take_children(&mut self, stack: &mut Vec<Value>) {
+        match self {
+            Value::Number(_) => {}
+            Value::List(items) => {
+                if let Some(items) = Rc::get_mut(items) {
+                    stack.append(items);
+                }
+            }
+            Value::Function(function) => {
+                if let Some(function) = Rc::get_mut(function) {
+                    stack.extend(function.captured.drain().map(|(_, value)| value));
+                }
+            }
+        }
+    }
+}
+
+/// drops nested values one at a time.
+impl Drop for Value {
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        self.take_children(&mut stack);
+
+        // a shared list or function is only released here, the value
+        // dropping it last takes its children then.
+        while let Some(mut value) = stack.pop() {
+            value.take_children(&mut stack);
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+// what is left to print of a value.
+enum Pending<'v> {
+    Value(&'v Value),
+    Function(&'v Function),
+    // names of captured variables, quoted.
+    Name(&'v str),
+    Text(&'static str),
+}
+
+// pushes values to print, separated by commas.
+fn push_separated<'v>(pending: &mut Vec<Pending<'v>>, values: &'v [Value]) {
+    for (i, value) in values.iter().enumerate().rev() {
+        pending.push(Pending::Value(value));
+        if i > 0 {
+            pending.push(Pending::Text(", "));
+        }
+    }
+}
+
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // pieces are pushed in reverse, so they are printed in order.
+        let mut pending = vec![Pending::Value(self)];
+
+        while let Some(piece) = pending.pop() {
+            match piece {
+                Pending::Text(text) => f.write_str(text)?,
+                Pending::Name(name) => f.write_str(name)?,
+                Pending::Value(Value::Number(n)) => write!(f, "{n}")?,
+                Pending::Value(Value::List(values)) => {
+                    write!(f, "[")?;
+                    pending.push(Pending::Text("]"));
+                    push_separated(&mut pending, values);
+                }
+                Pending::Value(Value::Function(function)) => {
+                    pending.push(Pending::Function(function))
+                }
+                Pending::Function(function) => {
+                    write!(f, "<function({})>", function.parameters.join(", "))?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// fmt debug for values, written like the derived implementation but always on one line.
+impl core::fmt::Debug for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        debug(Pending::Value(self), f)
+    }
+}
+
+/// fmt debug for functions, written like the derived implementation but always on one line.
+impl core::fmt::Debug for Function {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        debug(Pending::Function(self), f)
+    }
+}
+
+// writes the debug format of a value or a function, with the values
+// captured by functions in a stack instead of recursing.
+fn debug(piece: Pending<'_>, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    // pieces are pushed in reverse, so they are written in order.
+    let mut pending = vec![piece];
+
+    while let Some(piece) = pending.pop() {
+        match piece {
+            Pending::Text(text) => f.write_str(text)?,
+            Pending::Name(name) => write!(f, "{name:?}")?,
+            Pending::Value(Value::Number(n)) => write!(f, "Number({n:?})")?,
+            Pending::Value(Value::List(values)) => {
+                write!(f, "List([")?;
+                pending.push(Pending::Text("])"));
+                push_separated(&mut pending, values);
+            }
+            Pending::Value(Value::Function(function)) => {
+                write!(f, "Function(")?;
+                pending.push(Pending::Text(")"));
+                pending.push(Pending::Function(function));
+            }
+            Pending::Function(function) => {
+                write!(
+                    f,
+                    "Function {{ parameters: {:?}, body: {:?}, captured: {{",
+                    function.parameters, function.body
+                )?;
+                pending.push(Pending::Text("} }"));
+                for (i, (name, value)) in function.captured.iter().enumerate() {
+                    if i > 0 {
+                        pending.push(Pending::Text(", "));
+                    }
+                    pending.push(Pending::Value(value));
+                    pending.push(Pending::Text(": "));
+                    pending.push(Pending::Name(name));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
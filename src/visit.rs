@@ -0,0 +1,277 @@
+use std::mem;
+use std::rc::Rc;
+
+use crate::ast::{BinaryOperator, PostfixOperator, Statement, UnaryOperator, AST};
+
+/// Walks an AST by reference, one method for each kind of node.
+/// Every method walks the children of its node by default, so a pass only
+/// overrides the nodes it cares about, calling the matching `walk_*` function
+/// to keep walking the children.
+/// Walking recurses once for every level of the tree, and the parser doesn't
+/// limit long chains like `1 + 1 + … + 1`, so walking one built from untrusted
+/// input can overflow the stack. Passes which only look at every node can
+/// iterate `preorder` instead, it keeps the nodes left to visit on the heap.
+/// # Examples
+/// ```
+/// use expr_solver::{ast::Statement, parse, visit::Visitor};
+///
+/// // collects every variable used in an expression.
+/// #[derive(Default)]
+/// struct Variables(Vec<String>);
+///
+/// impl Visitor for Variables {
+///     fn visit_var(&mut self, name: &str) {
+///         self.0.push(name.to_string());
+///     }
+/// }
+///
+/// let mut variables = Variables::default();
+/// for statement in parse("x + f(y, 2) * x").unwrap() {
+///     variables.visit_statement(&statement);
+/// }
+/// assert_eq!(variables.0, ["x", "y", "x"]);
+/// ```
+pub trait Visitor {
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_ast(&mut self, ast: &AST) {
+        walk_ast(self, ast)
+    }
+
+    fn visit_literal(&mut self, _value: f64) {}
+
+    fn visit_var(&mut self, _name: &str) {}
+
+    fn visit_unary(&mut self, _op: UnaryOperator, operand: &AST) {
+        self.visit_ast(operand)
+    }
+
+    fn visit_postfix(&mut self, _op: PostfixOperator, operand: &AST) {
+        self.visit_ast(operand)
+    }
+
+    fn visit_binary(&mut self, _op: BinaryOperator, lhs: &AST, rhs: &AST) {
+        self.visit_ast(lhs);
+        self.visit_ast(rhs);
+    }
+
+    fn visit_call(&mut self, _name: &str, arguments: &[AST]) {
+        for argument in arguments {
+            self.visit_ast(argument);
+        }
+    }
+
+    fn visit_let(&mut self, _name: &str, value: &AST, body: &AST) {
+        self.visit_ast(value);
+        self.visit_ast(body);
+    }
+
+    fn visit_lambda(&mut self, _parameters: &[String], body: &AST) {
+        self.visit_ast(body)
+    }
+
+    fn visit_list(&mut self, items: &[AST]) {
+        for item in items {
+            self.visit_ast(item);
+        }
+    }
+}
+
+/// Walks the expressions of a statement.
+/// # Arguments
+/// * visitor - the pass walking the statement.
+/// * statement - the statement to walk.
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Function(_, _, body) => visitor.visit_ast(body),
+        Statement::Assignment(_, value) => visitor.visit_ast(value),
+        Statement::Expression(ast) => visitor.visit_ast(ast),
+    }
+}
+
+/// Calls the method of the visitor matching the kind of the node.
+/// # Arguments
+/// * visitor - the pass walking the tree.
+/// * ast - the node to visit.
+pub fn walk_ast<V: Visitor + ?Sized>(visitor: &mut V, ast: &AST) {
+    match ast {
+        AST::Literal(value) => visitor.visit_literal(*value),
+        AST::Var(name) => visitor.visit_var(name),
+        AST::Unary { op, operand } => visitor.visit_unary(*op, operand),
+        AST::Postfix { op, operand } => visitor.visit_postfix(*op, operand),
+        AST::Binary { op, lhs, rhs } => visitor.visit_binary(*op, lhs, rhs),
+        AST::Call(name, arguments) => visitor.visit_call(name, arguments),
+        AST::Let(name, value, body) => visitor.visit_let(name, value, body),
+        AST::Lambda(parameters, body) => visitor.visit_lambda(parameters, body),
+        AST::List(items) => visitor.visit_list(items),
+    }
+}
+
+/// Rewrites an AST, taking it by value and building a new one, one method
+/// for each kind of node.
+/// Every method folds the children of its node and rebuilds the same node by
+/// default, so a pass only overrides the nodes it rewrites.
+/// Folding recurses once for every level of the tree, like `Visitor` it can
+/// overflow the stack on long chains from untrusted input, unless it runs
+/// on a thread with a stack big enough for them.
+/// # Examples
+/// ```
+/// use expr_solver::{ast::{BinaryOperator, AST}, interpreter::Interpreter, visit::Fold};
+///
+/// // replaces every `x` with a number.
+/// struct Substitute(f64);
+///
+/// impl Fold for Substitute {
+///     fn fold_var(&mut self, name: String) -> AST {
+///         match name.as_str() {
+///             "x" => AST::Literal(self.0),
+///             _ => AST::Var(name),
+///         }
+///     }
+/// }
+///
+/// let ast = AST::binary(BinaryOperator::Multiply, AST::Var("x".to_string()), AST::Literal(3.0));
+/// let ast = Substitute(2.0).fold_ast(ast);
+/// assert_eq!(Interpreter::walk_ast(&ast), Ok(6.0));
+/// ```
+pub trait Fold {
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        fold_statement(self, statement)
+    }
+
+    fn fold_ast(&mut self, ast: AST) -> AST {
+        fold_ast(self, ast)
+    }
+
+    fn fold_literal(&mut self, value: f64) -> AST {
+        AST::Literal(value)
+    }
+
+    fn fold_var(&mut self, name: String) -> AST {
+        AST::Var(name)
+    }
+
+    fn fold_unary(&mut self, op: UnaryOperator, operand: AST) -> AST {
+        AST::unary(op, self.fold_ast(operand))
+    }
+
+    fn fold_postfix(&mut self, op: PostfixOperator, operand: AST) -> AST {
+        AST::postfix(op, self.fold_ast(operand))
+    }
+
+    fn fold_binary(&mut self, op: BinaryOperator, lhs: AST, rhs: AST) -> AST {
+        let lhs = self.fold_ast(lhs);
+        AST::binary(op, lhs, self.fold_ast(rhs))
+    }
+
+    fn fold_call(&mut self, name: String, arguments: Vec<AST>) -> AST {
+        let arguments = arguments
+            .into_iter()
+            .map(|argument| self.fold_ast(argument))
+            .collect();
+        AST::Call(name, arguments)
+    }
+
+    fn fold_let(&mut self, name: String, value: AST, body: AST) -> AST {
+        let value = self.fold_ast(value);
+        AST::Let(name, Box::new(value), Box::new(self.fold_ast(body)))
+    }
+
+    fn fold_lambda(&mut self, parameters: Vec<String>, body: AST) -> AST {
+        AST::Lambda(parameters, Rc::new(self.fold_ast(body)))
+    }
+
+    fn fold_list(&mut self, items: Vec<AST>) -> AST {
+        AST::List(items.into_iter().map(|item| self.fold_ast(item)).collect())
+    }
+}
+
+/// Folds the expressions of a statement.
+/// # Arguments
+/// * folder - the pass rewriting the statement.
+/// * statement - the statement to rewrite.
+/// # Returns
+/// * Statement - the same kind of statement with its expressions folded.
+pub fn fold_statement<F: Fold + ?Sized>(folder: &mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Function(name, parameters, body) => {
+            let body = folder.fold_ast(unshare(body));
+            Statement::Function(name, parameters, Rc::new(body))
+        }
+        Statement::Assignment(name, value) => Statement::Assignment(name, folder.fold_ast(value)),
+        Statement::Expression(ast) => Statement::Expression(folder.fold_ast(ast)),
+    }
+}
+
+/// Takes the node apart and calls the method of the folder matching its kind.
+/// # Arguments
+/// * folder - the pass rewriting the tree.
+/// * ast - the node to fold.
+/// # Returns
+/// * AST - the folded node.
+pub fn fold_ast<F: Fold + ?Sized>(folder: &mut F, mut ast: AST) -> AST {
+    // the tree drops its children itself, so they are taken out of the node
+    // instead of being moved out by the pattern.
+    match &mut ast {
+        AST::Literal(value) => folder.fold_literal(*value),
+        AST::Var(name) => folder.fold_var(mem::take(name)),
+        AST::Unary { op, operand } => folder.fold_unary(*op, take(operand)),
+        AST::Postfix { op, operand } => folder.fold_postfix(*op, take(operand)),
+        AST::Binary { op, lhs, rhs } => folder.fold_binary(*op, take(lhs), take(rhs)),
+        AST::Call(name, arguments) => folder.fold_call(mem::take(name), mem::take(arguments)),
+        AST::Let(name, value, body) => folder.fold_let(mem::take(name), take(value), take(body)),
+        AST::Lambda(parameters, body) => {
+            let body = mem::replace(body, Rc::new(AST::Literal(0.0)));
+            folder.fold_lambda(mem::take(parameters), unshare(body))
+        }
+        AST::List(items) => folder.fold_list(mem::take(items)),
+    }
+}
+
+/// Takes a child out of its node, leaving a literal in its place.
+fn take(child: &mut Box<AST>) -> AST {
+    mem::replace(child.as_mut(), AST::Literal(0.0))
+}
+
+/// Takes a shared tree, cloning it only if someone else still holds it.
+fn unshare(ast: Rc<AST>) -> AST {
+    Rc::try_unwrap(ast).unwrap_or_else(|shared| (*shared).clone())
+}
+
+/// Iterates over the nodes of a tree in preorder, without recursing,
+/// so trees of any depth can be inspected.
+/// # Arguments
+/// * ast - the tree.
+/// # Returns
+/// * `impl Iterator<Item = &AST>` - every node, each one before its children.
+/// # Examples
+/// ```
+/// use expr_solver::{ast::AST, parse_expression, visit::preorder};
+///
+/// let ast = parse_expression("x + f(y, 2) * x").unwrap();
+/// let variables: Vec<&AST> = preorder(&ast)
+///     .filter(|node| matches!(node, AST::Var(_)))
+///     .collect();
+/// assert_eq!(variables.len(), 3);
+/// ```
+pub fn preorder(ast: &AST) -> impl Iterator<Item = &AST> {
+    let mut pending = vec![ast];
+
+    std::iter::from_fn(move || {
+        let ast = pending.pop()?;
+        // children are pushed in reverse, so they come in order.
+        match ast {
+            AST::Literal(_) | AST::Var(_) => {}
+            AST::Unary { operand, .. } | AST::Postfix { operand, .. } => pending.push(operand),
+            AST::Binary { lhs, rhs, .. } | AST::Let(_, lhs, rhs) => {
+                pending.push(rhs);
+                pending.push(lhs);
+            }
+            AST::Call(_, children) | AST::List(children) => pending.extend(children.iter().rev()),
+            AST::Lambda(_, body) => pending.push(body),
+        }
+        Some(ast)
+    })
+}
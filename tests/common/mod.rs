@@ -0,0 +1,98 @@
+// every test file uses only some of the helpers.
+#![allow(dead_code)]
+
+use expr_solver::ast::{BinaryOperator, BinaryOperator::*, PostfixOperator, UnaryOperator, AST};
+use proptest::prelude::*;
+use std::rc::Rc;
+
+// parses a single expression, panicking on errors.
+pub fn parse_expression(input: &str) -> AST {
+    expr_solver::parse_expression(input)
+        .unwrap_or_else(|diagnostic| panic!("{input} : {diagnostic}"))
+}
+
+// nodes on the longest path of the deep trees below, far more than the call
+// stack could recurse through, so traversals must keep their pending work
+// on the heap to get through them.
+pub const DEEP: usize = 100_000;
+
+// `1 op (1 op (… op 1))`, nested `DEEP` times.
+pub fn deep_binary(op: BinaryOperator) -> AST {
+    let mut ast = AST::Literal(1.0);
+    for _ in 0..DEEP {
+        ast = AST::binary(op, AST::Literal(1.0), ast);
+    }
+    ast
+}
+
+// `op op … op 1`, nested `DEEP` times.
+pub fn deep_unary(op: UnaryOperator) -> AST {
+    let mut ast = AST::Literal(1.0);
+    for _ in 0..DEEP {
+        ast = AST::unary(op, ast);
+    }
+    ast
+}
+
+// strategies shared by the property tests.
+
+fn name() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9_]{0,3}".prop_filter("keywords aren't names", |name| {
+        !matches!(name.as_str(), "let" | "in")
+    })
+}
+
+fn binary_operator() -> impl Strategy<Value = BinaryOperator> {
+    prop::sample::select(vec![
+        Add,
+        Subtract,
+        Multiply,
+        Divide,
+        Power,
+        Less,
+        LessEqual,
+        Greater,
+        GreaterEqual,
+        Equal,
+        NotEqual,
+    ])
+}
+
+fn unary_operator() -> impl Strategy<Value = UnaryOperator> {
+    prop::sample::select(vec![
+        UnaryOperator::Plus,
+        UnaryOperator::Negate,
+        UnaryOperator::Root,
+    ])
+}
+
+// trees the parser could produce, numbers are never negative.
+pub fn ast() -> impl Strategy<Value = AST> {
+    let leaf = prop_oneof![
+        (0.0..1e20_f64).prop_map(AST::Literal),
+        prop::sample::select(vec![0.0, 1.0, 1e-9, 1e300]).prop_map(AST::Literal),
+        name().prop_map(AST::Var),
+    ];
+
+    leaf.prop_recursive(6, 48, 3, |inner| {
+        prop_oneof![
+            (unary_operator(), inner.clone()).prop_map(|(op, operand)| AST::unary(op, operand)),
+            inner
+                .clone()
+                .prop_map(|operand| AST::postfix(PostfixOperator::Factorial, operand)),
+            (binary_operator(), inner.clone(), inner.clone())
+                .prop_map(|(op, lhs, rhs)| AST::binary(op, lhs, rhs)),
+            (name(), prop::collection::vec(inner.clone(), 0..3))
+                .prop_map(|(name, arguments)| AST::Call(name, arguments)),
+            (name(), inner.clone(), inner.clone()).prop_map(|(name, value, body)| AST::Let(
+                name,
+                value.into(),
+                body.into()
+            )),
+            (prop::collection::btree_set(name(), 0..3), inner.clone()).prop_map(
+                |(parameters, body)| AST::Lambda(parameters.into_iter().collect(), Rc::new(body))
+            ),
+            prop::collection::vec(inner, 0..3).prop_map(AST::List),
+        ]
+    })
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use common::{ast, deep_binary, parse_expression};
+use expr_solver::{
+    ast::{BinaryOperator::*, AST},
+    binary::{decode, encode, FORMAT_VERSION, MAGIC},
+};
+use proptest::prelude::*;
+
+// builds a blob around a payload, with a valid header and checksum.
+fn blob(payload: &[u8]) -> Vec<u8> {
+    // the standard CRC-32, the one zip and PNG use.
+    let mut crc = !0u32;
+    for byte in payload {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(FORMAT_VERSION);
+    bytes.extend((payload.len() as u32).to_le_bytes());
+    bytes.extend((!crc).to_le_bytes());
+    bytes.extend(payload);
+    bytes
+}
+
+#[test]
+fn encoding() {
+    let bytes = encode(&AST::binary(
+        Add,
+        AST::Var("x".to_string()),
+        AST::Literal(1.0),
+    ))
+    .unwrap();
+
+    assert_eq!(
+        bytes,
+        blob(
+            &[
+                [1, 1, 0, 0, 0, b'x'].as_slice(),
+                &[0],
+                &1f64.to_le_bytes(),
+                &[4, 0],
+            ]
+            .concat()
+        )
+    );
+}
+
+#[test]
+fn round_trips() {
+    for input in [
+        "2 * x + 1",
+        "-n! / √2 <= 3 ^ 2 ^ 1",
+        "let z = [1, f(x, y), g()] in map((a, b) => a != b, z)",
+        "1e999 - 0.1",
+    ] {
+        let ast = parse_expression(input);
+        assert_eq!(decode(&encode(&ast).unwrap()), Ok(ast), "{input}");
+    }
+
+    let chain = deep_binary(Subtract);
+    assert_eq!(decode(&encode(&chain).unwrap()), Ok(chain));
+}
+
+#[test]
+fn invalid_headers() {
+    let bytes = encode(&parse_expression("x ^ 2")).unwrap();
+
+    assert_eq!(
+        decode(b"( +   x   2 )"),
+        Err("Not an encoded expression, it doesn't start with 'EXPR'.".to_string())
+    );
+    assert_eq!(
+        decode(&bytes[..8]),
+        Err("Encoded expression is truncated, the header is incomplete.".to_string())
+    );
+
+    let mut newer = bytes.clone();
+    newer[4] = FORMAT_VERSION + 1;
+    assert_eq!(
+        decode(&newer),
+        Err(format!(
+            "Unsupported format version {}, expected {FORMAT_VERSION}.",
+            FORMAT_VERSION + 1
+        ))
+    );
+
+    assert_eq!(
+        decode(&bytes[..bytes.len() - 1]),
+        Err(
+            "Encoded expression is truncated or padded, expected 17 byte(s) of payload, found 16."
+                .to_string()
+        )
+    );
+    let mut padded = bytes.clone();
+    padded.push(0);
+    assert!(decode(&padded).unwrap_err().contains("found 18"));
+}
+
+#[test]
+fn corrupted_payloads() {
+    let bytes = encode(&parse_expression("x ^ 2")).unwrap();
+
+    // flipping any bit of the payload is caught by the checksum.
+    for i in 13..bytes.len() {
+        for bit in 0..8 {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 1 << bit;
+            assert_eq!(
+                decode(&corrupted),
+                Err("Encoded expression is corrupted, its checksum doesn't match.".to_string())
+            );
+        }
+    }
+}
+
+#[test]
+fn invalid_payloads() {
+    // payloads with a valid checksum, which still don't build a tree.
+    let read = |payload: &[u8]| decode(&blob(payload)).unwrap_err();
+
+    assert_eq!(
+        read(&[9]),
+        "Unknown instruction 9 at byte 0 of the payload."
+    );
+    assert_eq!(
+        read(&[1, 1, 0, 0, 0, b'x', 4, 11]),
+        "Unknown operator 11 at byte 7 of the payload."
+    );
+    assert_eq!(
+        read(&[1, 5, 0, 0, 0, b'x']),
+        "Expected 5 more byte(s) at byte 5 of the payload, found 1."
+    );
+    assert_eq!(
+        read(&[1, 1, 0, 0, 0, 0xff]),
+        "Invalid UTF-8 in name at byte 1 of the payload."
+    );
+    assert_eq!(
+        read(&[1, 1, 0, 0, 0, b'x', 4, 0]),
+        "Expected 2 node(s) before the instruction, found 1."
+    );
+    assert_eq!(
+        read(&[]),
+        "Expected instructions building one tree, they build 0."
+    );
+
+    // counts can't make the decoder allocate more than the payload holds.
+    assert_eq!(
+        read(&[7, 0xff, 0xff, 0xff, 0xff]),
+        "Expected 4 more byte(s) at byte 5 of the payload, found 0."
+    );
+}
+
+proptest! {
+    #[test]
+    fn trees_round_trip(ast in ast()) {
+        prop_assert_eq!(decode(&encode(&ast).unwrap()), Ok(ast));
+    }
+}
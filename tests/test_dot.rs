@@ -0,0 +1,153 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use common::{deep_unary, parse_expression, DEEP};
+use expr_solver::{
+    ast::{UnaryOperator, AST},
+    dot::{to_dot, to_dot_with_values},
+    interpreter::Interpreter,
+    observer::Observer,
+    parse,
+    value::Value,
+};
+
+// returns the node and edge lines of a graph, without the header.
+fn body(graph: &str) -> Vec<String> {
+    graph
+        .lines()
+        .skip(3)
+        .take_while(|line| *line != "}")
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn graph() {
+    let graph = to_dot(&parse_expression("-f(x, 2)!"));
+    assert!(graph.starts_with("digraph AST {\n"));
+    assert!(graph.ends_with("}\n"));
+    assert_eq!(
+        body(&graph),
+        [
+            r#"n0 [label="-"];"#,
+            r#"n1 [label="!"];"#,
+            "n0 -> n1;",
+            r#"n2 [label="f()"];"#,
+            "n1 -> n2;",
+            r#"n3 [label="x"];"#,
+            "n2 -> n3;",
+            r#"n4 [label="2"];"#,
+            "n2 -> n4;",
+        ]
+    );
+
+    assert_eq!(
+        body(&to_dot(&parse_expression("let y = [1] in (a, b) => y"))),
+        [
+            r#"n0 [label="let y"];"#,
+            r#"n1 [label="[ ]"];"#,
+            r#"n0 -> n1 [label="value"];"#,
+            r#"n2 [label="1"];"#,
+            "n1 -> n2;",
+            r#"n3 [label="(a, b) =>"];"#,
+            r#"n0 -> n3 [label="body"];"#,
+            r#"n4 [label="y"];"#,
+            "n3 -> n4;",
+        ]
+    );
+}
+
+#[test]
+fn values() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&parse("x = 3").unwrap()).unwrap();
+
+    assert_eq!(
+        body(&to_dot_with_values(
+            &parse_expression("2 * x"),
+            &mut interpreter
+        )),
+        [
+            r#"n0 [label="*\n= 6"];"#,
+            r#"n1 [label="2\n= 2"];"#,
+            "n0 -> n1;",
+            r#"n2 [label="x\n= 3"];"#,
+            "n0 -> n2;",
+        ]
+    );
+
+    // the body is evaluated with the binding.
+    assert_eq!(
+        body(&to_dot_with_values(
+            &parse_expression("let y = 1 in y"),
+            &mut interpreter
+        )),
+        [
+            r#"n0 [label="let y\n= 1"];"#,
+            r#"n1 [label="1\n= 1"];"#,
+            r#"n0 -> n1 [label="value"];"#,
+            r#"n2 [label="y\n= 1"];"#,
+            r#"n0 -> n2 [label="body"];"#,
+        ]
+    );
+
+    // bodies of anonymous functions have a value for every call, so none is shown,
+    // and nodes after a failure have none either.
+    assert_eq!(
+        body(&to_dot_with_values(
+            &parse_expression("[map(v => v, [x]), z]"),
+            &mut interpreter
+        )),
+        [
+            r#"n0 [label="[ ]"];"#,
+            r#"n1 [label="map()\n= [3]"];"#,
+            "n0 -> n1;",
+            r#"n2 [label="(v) =>\n= <function(v)>"];"#,
+            "n1 -> n2;",
+            r#"n3 [label="v"];"#,
+            "n2 -> n3;",
+            r#"n4 [label="[ ]\n= [3]"];"#,
+            "n1 -> n4;",
+            r#"n5 [label="x\n= 3"];"#,
+            "n4 -> n5;",
+            r#"n6 [label="z"];"#,
+            "n0 -> n6;",
+        ]
+    );
+}
+
+#[test]
+fn values_are_evaluated_once() {
+    // counts the nodes left.
+    #[derive(Default)]
+    struct Count(usize);
+
+    impl Observer for Count {
+        fn leave(&mut self, _: &AST, _: &Value) {
+            self.0 += 1;
+        }
+    }
+
+    let count = Rc::new(RefCell::new(Count::default()));
+    let mut interpreter = Interpreter::new().with_observer(count.clone()).with_trace();
+    to_dot_with_values(&parse_expression("1 + 2 * 3"), &mut interpreter);
+
+    // the interpreter's own hooks see a single evaluation.
+    assert_eq!(count.borrow().0, 5);
+    assert_eq!(interpreter.take_trace().len(), 2);
+    interpreter.evaluate(&parse_expression("4")).unwrap();
+    assert_eq!(count.borrow().0, 6);
+}
+
+#[test]
+fn escaping() {
+    let graph = to_dot(&AST::Var("say \"hi\"\\".to_string()));
+    assert_eq!(body(&graph), [r#"n0 [label="say \"hi\"\\"];"#]);
+}
+
+#[test]
+fn deep_trees() {
+    let edge = format!("n{} -> n{DEEP};", DEEP - 1);
+    assert!(to_dot(&deep_unary(UnaryOperator::Negate)).contains(&edge));
+}
@@ -0,0 +1,137 @@
+use expr_solver::{
+    interpreter::{FloatPolicy, Interpreter},
+    parse,
+};
+
+// evaluates an expression in strict mode.
+fn strict(input: &str) -> Result<f64, String> {
+    Interpreter::new()
+        .with_float_policy(FloatPolicy::Strict)
+        .run(&parse(input).unwrap())?
+        .as_number()
+}
+
+// evaluates an expression with IEEE semantics, the default.
+fn ieee(input: &str) -> f64 {
+    Interpreter::new()
+        .run(&parse(input).unwrap())
+        .unwrap()
+        .as_number()
+        .unwrap()
+}
+
+#[test]
+fn ieee_semantics() {
+    assert_eq!(ieee("1 / 0"), f64::INFINITY);
+    assert_eq!(ieee("-1 / 0"), f64::NEG_INFINITY);
+    assert!(ieee("0 / 0").is_nan());
+    assert!(ieee("√-1").is_nan());
+    assert!(ieee("ln(-1)").is_nan());
+    assert_eq!(ieee("200!"), f64::INFINITY);
+
+    // negative integers have no factorial, whatever the policy.
+    assert_eq!(
+        Interpreter::new()
+            .run(&parse("(-3)!").unwrap())
+            .unwrap_err()
+            .to_string(),
+        "Factorial of a negative integer in '(-3)!'."
+    );
+}
+
+#[test]
+fn division_by_zero() {
+    assert_eq!(
+        strict("1 / 0"),
+        Err("Division by zero in '1 / 0'.".to_string())
+    );
+    assert_eq!(
+        strict("2 * (0 / (1 - 1))"),
+        Err("Division by zero in '0 / (1 - 1)'.".to_string())
+    );
+    assert_eq!(strict("0 / 5"), Ok(0.0));
+}
+
+#[test]
+fn domain_errors() {
+    assert_eq!(
+        strict("1 + (-1)!"),
+        Err("Factorial of a negative integer in '(-1)!'.".to_string())
+    );
+    assert_eq!(
+        strict("√(1 - 2)"),
+        Err("Square root of a negative number in '√(1 - 2)'.".to_string())
+    );
+    assert_eq!(
+        strict("sqrt(-4)"),
+        Err("Square root of a negative number in 'sqrt(-4)'.".to_string())
+    );
+    assert_eq!(
+        strict("log(-10)"),
+        Err("Logarithm of a negative number in 'log(-10)'.".to_string())
+    );
+    assert_eq!(
+        strict("ln(0)"),
+        Err("Logarithm of zero in 'ln(0)'.".to_string())
+    );
+    assert_eq!(
+        strict("asin(2)"),
+        Err("Argument outside the domain of the function in 'asin(2)'.".to_string())
+    );
+
+    // negative numbers which aren't integers have a factorial.
+    assert_eq!(strict("(-0.5)! > 1"), Ok(1.0));
+
+    // errors name the sub-expression inside the function which caused them.
+    assert_eq!(
+        strict("f(x) = ln(x - 1); f(3) + f(1)"),
+        Err("Logarithm of zero in 'ln(x - 1)'.".to_string())
+    );
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(
+        strict("200! / 2"),
+        Err("Overflow to infinity in '200!'.".to_string())
+    );
+    assert_eq!(
+        strict("10 ^ 400"),
+        Err("Overflow to infinity in '10 ^ 400'.".to_string())
+    );
+    assert_eq!(
+        strict("exp(1000)"),
+        Err("Overflow to infinity in 'exp(1000)'.".to_string())
+    );
+    assert_eq!(strict("170! > 0"), Ok(1.0));
+}
+
+#[test]
+fn infinite_literals() {
+    // infinite operands may give infinite results.
+    assert_eq!(strict("1e999"), Ok(f64::INFINITY));
+    assert_eq!(strict("1e999 + 1"), Ok(f64::INFINITY));
+    assert_eq!(strict("-1e999 * 2"), Ok(f64::NEG_INFINITY));
+    assert_eq!(strict("exp(1e999)"), Ok(f64::INFINITY));
+    assert_eq!(strict("let y = 1e999 in y"), Ok(f64::INFINITY));
+
+    // so can functions returning them, only their body is checked.
+    assert_eq!(strict("f(x) = 1e999; f(1)"), Ok(f64::INFINITY));
+    assert_eq!(strict("f() = 1e999; f()"), Ok(f64::INFINITY));
+    assert_eq!(strict("g = x => 1e999; g(1)"), Ok(f64::INFINITY));
+    assert_eq!(
+        strict("f(x) = x * 1e999; f(0)"),
+        Err("Result is not a number in 'x * 1e999'.".to_string())
+    );
+    assert_eq!(strict("1 / 1e999"), Ok(0.0));
+
+    // but not results which aren't a number.
+    assert_eq!(
+        strict("1e999 - 1e999"),
+        Err("Result is not a number in '1e999 - 1e999'.".to_string())
+    );
+    assert_eq!(
+        strict("1e999 * 0"),
+        Err("Result is not a number in '1e999 * 0'.".to_string())
+    );
+}
@@ -1,14 +1,22 @@
-use expr_solver::{ast::AST::*, interpreter::Interpreter, token::Token::*};
+mod common;
+
+use common::{deep_binary, DEEP};
+use expr_solver::{
+    ast::{BinaryOperator::*, PostfixOperator::*, UnaryOperator::*, AST, AST::*},
+    interpreter::Interpreter,
+};
 
 #[test]
 fn basic_walking() {
-    let asts = vec![
-        Node(Number(2.0)),
-        Con(Minus, vec![Node(Number(2_f64))]),
-        Con(Minus, vec![Node(Number(2_f64)), Node(Number(2_f64))]),
-        Con(Plus, vec![Node(Number(2_f64)), Node(Number(2_f64))]),
-        Con(Slash, vec![Node(Number(2_f64)), Node(Number(2_f64))]),
-        Con(Star, vec![Node(Number(3_f64)), Node(Number(2_f64))]),
+    let asts = [
+        Literal(2.0),
+        AST::unary(Negate, Literal(2_f64)),
+        AST::binary(Subtract, Literal(2_f64), Literal(2_f64)),
+        AST::binary(Add, Literal(2_f64), Literal(2_f64)),
+        AST::binary(Divide, Literal(2_f64), Literal(2_f64)),
+        AST::binary(Multiply, Literal(3_f64), Literal(2_f64)),
+        AST::postfix(Factorial, Literal(3_f64)),
+        AST::unary(Plus, Literal(2_f64)),
     ];
 
     assert_eq!(Interpreter::walk_ast(&asts[0]), Ok(2.0));
@@ -17,4 +25,23 @@ fn basic_walking() {
     assert_eq!(Interpreter::walk_ast(&asts[3]), Ok(4.0));
     assert_eq!(Interpreter::walk_ast(&asts[4]), Ok(1.0));
     assert_eq!(Interpreter::walk_ast(&asts[5]), Ok(6.0));
+    assert_eq!(Interpreter::walk_ast(&asts[6]), Ok(6.0));
+    assert_eq!(Interpreter::walk_ast(&asts[7]), Ok(2.0));
+}
+
+#[test]
+fn deep_expressions() {
+    let chain = deep_binary(Add);
+    assert_eq!(Interpreter::walk_ast(&chain), Ok(DEEP as f64 + 1.0));
+    assert!(chain.clone() == chain);
+    assert!(chain.to_string().starts_with("( +   1 ( +   1 "));
+    assert!(format!("{chain:?}").starts_with("Binary { op: Add, lhs: Literal(1.0), rhs: Binary {"));
+
+    assert_eq!(
+        format!(
+            "{:?}",
+            Call("f".into(), vec![Literal(1.0), List(vec![Var("x".into())])])
+        ),
+        r#"Call("f", [Literal(1.0), List([Var("x")])])"#
+    );
 }
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{deep_binary, parse_expression};
+use expr_solver::{
+    ast::{BinaryOperator::*, AST},
+    latex::to_latex,
+};
+
+// parses an expression and renders it.
+fn latex(input: &str) -> String {
+    to_latex(&parse_expression(input))
+}
+
+#[test]
+fn operators() {
+    assert_eq!(latex("a + b * c"), r"a + b \cdot c");
+    assert_eq!(latex("(a + b) * c"), r"\left(a + b\right) \cdot c");
+    assert_eq!(latex("a - (b - c)"), r"a - \left(b - c\right)");
+    assert_eq!(latex("a <= b != c"), r"a \leq b \neq c");
+    assert_eq!(latex("-a * -b"), r"-a \cdot \left(-b\right)");
+    assert_eq!(latex("-(a + b)"), r"-\left(a + b\right)");
+}
+
+#[test]
+fn typeset_grouping() {
+    // fractions, roots and exponents group their contents.
+    assert_eq!(latex("(a + b) / (c - d)"), r"\frac{a + b}{c - d}");
+    assert_eq!(latex("-(a / b)"), r"-\frac{a}{b}");
+    assert_eq!(latex("x^(n + 1)"), r"x^{n + 1}");
+    assert_eq!(latex("√(x + 1)"), r"\sqrt{x + 1}");
+    assert_eq!(latex("2^3^4"), r"2^{3^{4}}");
+
+    // bases and factorials group anything but a single symbol.
+    assert_eq!(latex("(2^3)^4"), r"\left(2^{3}\right)^{4}");
+    assert_eq!(latex("(a / b)^2"), r"\left(\frac{a}{b}\right)^{2}");
+    assert_eq!(latex("(-x)^2"), r"\left(-x\right)^{2}");
+    assert_eq!(latex("-x^2"), r"-x^{2}");
+    assert_eq!(latex("n! + (n + 1)!"), r"n! + \left(n + 1\right)!");
+}
+
+#[test]
+fn functions_and_names() {
+    assert_eq!(latex("sin(x)"), r"\sin\left(x\right)");
+    assert_eq!(latex("atan(x)^2"), r"\arctan\left(x\right)^{2}");
+    assert_eq!(latex("sqrt(x) + abs(y)"), r"\sqrt{x} + \left|y\right|");
+    assert_eq!(latex("f(x, y)"), r"f\left(x, y\right)");
+    assert_eq!(latex("gcd(a, b)"), r"\operatorname{gcd}\left(a, b\right)");
+    assert_eq!(latex("2 * pi * r"), r"2 \cdot \pi \cdot r");
+    assert_eq!(latex("theta + rate_1"), r"\theta + \mathrm{rate\_1}");
+    assert_eq!(latex("[1, 2]"), r"\left[1, 2\right]");
+    assert_eq!(
+        latex("map(x => x^2, l)"),
+        r"\operatorname{map}\left(x \mapsto x^{2}, l\right)"
+    );
+    assert_eq!(
+        latex("let r = 2 in r^2"),
+        r"\operatorname{let} r = 2 \operatorname{in} r^{2}"
+    );
+
+    // names are escaped, whatever built the tree.
+    let ast = AST::Var(r"a{b}\c#$%&^~_".to_string());
+    assert_eq!(
+        to_latex(&ast),
+        r"\mathrm{a\{b\}\backslash{}c\#\$\%\&\hat{}\tilde{}\_}"
+    );
+    let ast = AST::Call("f}{x".to_string(), vec![AST::Var("%".to_string())]);
+    assert_eq!(to_latex(&ast), r"\operatorname{f\}\{x}\left(\%\right)");
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(latex("2.5"), "2.5");
+    assert_eq!(latex("1e300"), "10^{300}");
+    assert_eq!(latex("1.5e-9 * x"), r"1.5 \times 10^{-9} \cdot x");
+    assert_eq!(latex("1e999"), r"\infty");
+
+    // scientific notation is grouped as a base.
+    let ast = AST::binary(Power, AST::Literal(2e20), AST::Literal(2.0));
+    assert_eq!(to_latex(&ast), r"\left(2 \times 10^{20}\right)^{2}");
+}
+
+#[test]
+fn deep_trees() {
+    assert!(to_latex(&deep_binary(Divide)).starts_with(r"\frac{1}{\frac{1}{"));
+}
@@ -1,55 +1,204 @@
-use expr_solver::{lexer::Lexer, token::Token};
+use expr_solver::{
+    lexer::{LexError, Lexer},
+    token::{Span, Token},
+};
+
+// scans every token of the input, panics on lexical errors.
+fn tokens(input: &str) -> Vec<Token<'_>> {
+    Lexer::new(input)
+        .map(|token| token.expect("lexical error").token)
+        .collect()
+}
+
+// scans the input, keeping only the lexical errors.
+fn errors(input: &str) -> Vec<LexError> {
+    Lexer::new(input).filter_map(Result::err).collect()
+}
 
 #[test]
 fn basic() {
-    let mut lexer = Lexer::new(&"2 + 2".to_string());
-    lexer.scan();
+    let lexer = Lexer::new("2 + 2");
 
-    assert_eq!(lexer.len, 5);
-    assert_eq!(lexer.tokens.len(), 4);
+    assert_eq!(lexer.count(), 3);
 }
 
 #[test]
 fn basic_tokenization() {
-    let mut lexer = Lexer::new(&"2 ++  2".to_string());
-    lexer.scan();
+    let tokens = tokens("2 ++  2");
 
-    assert_eq!(lexer.tokens.len(), 5);
+    assert_eq!(tokens.len(), 4);
 
-    match lexer.tokens[4] {
+    match tokens[3] {
         Token::Number(n) => assert!(n == 2_f64, "Token is not 2."),
         _ => {
-            assert!(!true, "Token is not number.");
+            panic!("Token is not number.");
         }
     }
-    assert!(matches!(lexer.tokens[2], Token::Plus));
-    assert!(matches!(lexer.tokens[3], Token::Plus));
+    assert!(matches!(tokens[1], Token::Plus));
+    assert!(matches!(tokens[2], Token::Plus));
 }
 
 #[test]
 fn basic_number_scanning() {
-    let mut lexer = Lexer::new(&"1414141 141.141 141".to_string());
-    lexer.scan();
+    let tokens = tokens("1414141 141.141 141");
 
-    match lexer.tokens[3] {
+    match tokens[0] {
         Token::Number(n) => assert!(n == 1414141_f64, "Token is not 1414141."),
         _ => {
-            assert!(!true, "Token is not number.");
+            panic!("Token is not number.");
         }
     }
 
-    match lexer.tokens[2] {
+    match tokens[1] {
         Token::Number(n) => assert!(n == 141.141_f64, "Token is not 1414141."),
         _ => {
-            assert!(!true, "Token is not number.");
+            panic!("Token is not number.");
         }
     }
 }
 
 #[test]
 fn error_flag() {
-    let mut lexer = Lexer::new(&"2'2".to_string());
-    lexer.scan();
+    assert_eq!(errors("2'2").len(), 1);
+}
+
+#[test]
+fn spans() {
+    let spans: Vec<Span> = Lexer::new(" 12 +\tfoo")
+        .map(|token| token.unwrap().span)
+        .collect();
+
+    assert_eq!(
+        spans,
+        vec![
+            Span { start: 1, end: 3 },
+            Span { start: 4, end: 5 },
+            Span { start: 6, end: 9 },
+        ]
+    );
+}
+
+#[test]
+fn zero_copy_identifiers() {
+    let input = "alpha + beta";
+    let tokens = tokens(input);
+
+    match tokens[0] {
+        Token::Identifier(name) => assert_eq!(name.as_ptr(), input.as_ptr()),
+        _ => panic!("Token is not identifier."),
+    }
+}
+
+#[test]
+fn number_literals() {
+    let cases = [
+        ("1e-9", 1e-9),
+        ("6.02E23", 6.02e23),
+        ("2.5e+3", 2500.0),
+        ("0xFF", 255.0),
+        ("0Xff", 255.0),
+        ("0b1010", 10.0),
+        ("0o17", 15.0),
+        (".5", 0.5),
+        ("1_000_000", 1_000_000.0),
+        ("0xFF_FF", 65535.0),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(tokens(input), vec![Token::Number(expected)]);
+    }
+}
+
+#[test]
+fn malformed_number_literals() {
+    let cases = [
+        (
+            "1e",
+            "Invalid number literal '1e', expected digits in the exponent.",
+        ),
+        ("0xZ", "Invalid digit 'Z' in hexadecimal literal '0xZ'."),
+        (
+            "0b",
+            "Invalid binary literal '0b', expected digits after '0b'.",
+        ),
+        ("0o18", "Invalid digit '8' in octal literal '0o18'."),
+        (
+            "1_",
+            "Invalid number literal '1_', '_' must be between digits.",
+        ),
+    ];
+
+    for (input, expected) in cases {
+        let errors: Vec<String> = errors(input).into_iter().map(|e| e.message).collect();
+        assert_eq!(errors, vec![expected.to_string()]);
+    }
+}
+
+#[test]
+fn juxtaposed_exponents() {
+    // for implicit multiplication, an 'e' without exponent digits starts a name.
+    let tokens = |input| -> Vec<Token<'_>> {
+        Lexer::new(input)
+            .with_juxtaposition()
+            .map(|token| token.expect("lexical error").token)
+            .collect()
+    };
+
+    assert_eq!(
+        tokens("2e"),
+        vec![Token::Number(2.0), Token::Identifier("e")]
+    );
+    assert_eq!(
+        tokens("2exp"),
+        vec![Token::Number(2.0), Token::Identifier("exp")]
+    );
+    assert_eq!(
+        tokens("2e-x"),
+        vec![
+            Token::Number(2.0),
+            Token::Identifier("e"),
+            Token::Minus,
+            Token::Identifier("x")
+        ]
+    );
+    assert_eq!(tokens("2e-3"), vec![Token::Number(2e-3)]);
+}
+
+#[test]
+fn unicode_aliases() {
+    assert_eq!(
+        tokens("2×π÷√4−1"),
+        vec![
+            Token::Number(2.0),
+            Token::Star,
+            Token::Identifier("pi"),
+            Token::Slash,
+            Token::Root,
+            Token::Number(4.0),
+            Token::Minus,
+            Token::Number(1.0),
+        ]
+    );
+}
+
+#[test]
+fn unicode_input_never_panics() {
+    for input in [
+        "é+2", "２", "ß2.5", "2.😀", "0x😀", "1e😀", "√", "😀😀", "\u{0}1", "1\u{0}",
+    ] {
+        Lexer::new(input).for_each(drop);
+    }
+
+    assert_eq!(
+        tokens("é+2"),
+        vec![Token::Identifier("é"), Token::Plus, Token::Number(2.0)]
+    );
 
-    assert!(lexer.has_errors);
+    assert_eq!(
+        errors("２"),
+        vec![LexError {
+            message: "Unexpected character '２'.".to_string(),
+            span: Span { start: 0, end: 3 },
+        }]
+    );
 }
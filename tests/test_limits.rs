@@ -0,0 +1,179 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use expr_solver::{
+    interpreter::{EvalError, Interpreter, MAX_CALL_DEPTH},
+    limits::{LimitExceeded, Limits},
+    parse,
+};
+
+// evaluates a program with the given limits, returning the error message
+// and the exceeded limit.
+fn exceed(input: &str, limits: Limits) -> (String, Option<LimitExceeded>) {
+    let mut interpreter = Interpreter::new().with_limits(limits);
+    let error = interpreter.run(&parse(input).unwrap()).unwrap_err();
+    let limit = match &error {
+        EvalError::Limit(limit) => Some(limit.clone()),
+        EvalError::Message(_) => None,
+    };
+    (error.to_string(), limit)
+}
+
+// a program doing millions of operations.
+const HEAVY: &str = "l = map(x => x, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    map(a => map(b => map(c => map(d => map(e => map(f => a * b * c * d * e * f, l), l), l), l), l), l)";
+
+#[test]
+fn operations() {
+    let limits = Limits {
+        max_operations: Some(10),
+        ..Default::default()
+    };
+
+    // `1 + 2 * 3` evaluates 5 nodes.
+    let mut interpreter = Interpreter::new().with_limits(limits.clone());
+    assert!(interpreter.run(&parse("1 + 2 * 3").unwrap()).is_ok());
+    assert!(interpreter
+        .run(&parse("1 + 2 * 3 - 4 / 5").unwrap())
+        .is_ok());
+
+    // the budget is per evaluation, functions count against their caller.
+    assert_eq!(
+        exceed("f(x) = x * x * x; f(1) + f(2)", limits),
+        (
+            "Evaluation exceeded the limit of 10 operations.".to_string(),
+            Some(LimitExceeded::Operations(10))
+        )
+    );
+}
+
+#[test]
+fn call_depth() {
+    let (message, limit) = exceed("f(x) = f(x); f(1)", Limits::default());
+    assert_eq!(
+        message,
+        format!("Maximum call depth of {MAX_CALL_DEPTH} exceeded, recursive definition?")
+    );
+    assert_eq!(limit, Some(LimitExceeded::CallDepth(MAX_CALL_DEPTH)));
+
+    let limits = Limits {
+        max_call_depth: 2,
+        ..Default::default()
+    };
+    let (_, limit) = exceed("f(x) = g(x); g(x) = h(x); h(x) = x; f(1)", limits);
+    assert_eq!(limit, Some(LimitExceeded::CallDepth(2)));
+
+    // calls nest on the stack, deeper limits are capped.
+    let limits = Limits {
+        max_call_depth: 100_000,
+        ..Default::default()
+    };
+    let (message, limit) = exceed("f(x) = f(x); f(1)", limits);
+    assert_eq!(
+        message,
+        format!("Maximum call depth of {MAX_CALL_DEPTH} exceeded, recursive definition?")
+    );
+    assert_eq!(limit, Some(LimitExceeded::CallDepth(MAX_CALL_DEPTH)));
+
+    // nesting set with `with_max_depth` is reported as well.
+    let mut interpreter = Interpreter::new().with_max_depth(2);
+    assert_eq!(
+        interpreter.run(&parse("-(-(-1))").unwrap()).unwrap_err(),
+        EvalError::Limit(LimitExceeded::Nesting(2))
+    );
+}
+
+#[test]
+fn time() {
+    let max_time = Duration::from_millis(10);
+    let started = Instant::now();
+    let (message, limit) = exceed(
+        HEAVY,
+        Limits {
+            max_time: Some(max_time),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(message, "Evaluation exceeded the time limit of 10ms.");
+    assert_eq!(limit, Some(LimitExceeded::Time(max_time)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn sizes() {
+    let limits = Limits {
+        max_magnitude: Some(1e9),
+        max_list_length: Some(3),
+        ..Default::default()
+    };
+
+    let mut interpreter = Interpreter::new().with_limits(limits.clone());
+    assert!(interpreter.run(&parse("12! / 1000").unwrap()).is_ok());
+    assert!(interpreter.run(&parse("13! / 1000").unwrap()).is_err());
+    assert!(interpreter.run(&parse("[1, 2, 1e9]").unwrap()).is_ok());
+
+    // intermediate values count, even if the result is small.
+    assert_eq!(
+        exceed("20! / 20!", limits.clone()),
+        (
+            "Value exceeded the maximum magnitude of 1000000000.".to_string(),
+            Some(LimitExceeded::Magnitude(1e9))
+        )
+    );
+    assert_eq!(
+        exceed("1 / 0", limits.clone()).1,
+        Some(LimitExceeded::Magnitude(1e9))
+    );
+    assert_eq!(
+        exceed("map(x => x, [1, 2, 3, 4])", limits.clone()),
+        (
+            "List exceeded the maximum length of 3.".to_string(),
+            Some(LimitExceeded::ListLength(3))
+        )
+    );
+
+    // failing for any other reason isn't a limit.
+    let mut interpreter = Interpreter::new().with_limits(limits);
+    assert!(interpreter.run(&parse("20!").unwrap()).is_err());
+    assert!(matches!(
+        interpreter.run(&parse("y").unwrap()),
+        Err(EvalError::Message(_))
+    ));
+}
+
+#[test]
+fn cancellation() {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let limits = Limits {
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    };
+
+    let canceller = {
+        let cancel = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            cancel.store(true, Ordering::Relaxed);
+        })
+    };
+
+    let started = Instant::now();
+    let (message, limit) = exceed(HEAVY, limits.clone());
+    canceller.join().unwrap();
+
+    assert_eq!(message, "Evaluation was cancelled.");
+    assert_eq!(limit, Some(LimitExceeded::Cancelled));
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    // the flag stays set till it is cleared.
+    assert_eq!(
+        exceed("1 + 1", limits.clone()).1,
+        Some(LimitExceeded::Cancelled)
+    );
+    cancel.store(false, Ordering::Relaxed);
+    let mut interpreter = Interpreter::new().with_limits(limits);
+    assert!(interpreter.run(&parse("1 + 1").unwrap()).is_ok());
+}
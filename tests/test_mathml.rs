@@ -0,0 +1,123 @@
+mod common;
+
+use common::{deep_unary, parse_expression};
+use expr_solver::{
+    ast::{UnaryOperator::Root, AST},
+    mathml::to_mathml,
+};
+
+// parses an expression and renders it, without the surrounding `<math>`.
+fn mathml(input: &str) -> String {
+    strip(&to_mathml(&parse_expression(input)))
+}
+
+// removes the surrounding `<math>` element.
+fn strip(math: &str) -> String {
+    math.strip_prefix(r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#)
+        .and_then(|math| math.strip_suffix("</math>"))
+        .expect("a math element")
+        .to_string()
+}
+
+#[test]
+fn operators() {
+    assert_eq!(
+        mathml("a + b * c"),
+        "<mrow><mi>a</mi><mo>+</mo><mrow><mi>b</mi><mo>⋅</mo><mi>c</mi></mrow></mrow>"
+    );
+    assert_eq!(
+        mathml("(a - b) * c"),
+        "<mrow><mrow><mo>(</mo><mrow><mi>a</mi><mo>−</mo><mi>b</mi></mrow><mo>)</mo></mrow>\
+         <mo>⋅</mo><mi>c</mi></mrow>"
+    );
+    assert_eq!(
+        mathml("a <= b"),
+        "<mrow><mi>a</mi><mo>≤</mo><mi>b</mi></mrow>"
+    );
+    assert_eq!(
+        mathml("a < b"),
+        "<mrow><mi>a</mi><mo>&lt;</mo><mi>b</mi></mrow>"
+    );
+    assert_eq!(mathml("-x"), "<mrow><mo>−</mo><mi>x</mi></mrow>");
+    assert_eq!(mathml("n!"), "<mrow><mi>n</mi><mo>!</mo></mrow>");
+    assert_eq!(
+        mathml("(n + 1)!"),
+        "<mrow><mrow><mo>(</mo><mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow><mo>)</mo></mrow>\
+         <mo>!</mo></mrow>"
+    );
+}
+
+#[test]
+fn layout() {
+    assert_eq!(
+        mathml("(a + b) / 2"),
+        "<mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mn>2</mn></mfrac>"
+    );
+    assert_eq!(
+        mathml("x^(n + 1)"),
+        "<msup><mi>x</mi><mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msup>"
+    );
+    assert_eq!(
+        mathml("(-x)^2"),
+        "<msup><mrow><mo>(</mo><mrow><mo>−</mo><mi>x</mi></mrow><mo>)</mo></mrow><mn>2</mn></msup>"
+    );
+    assert_eq!(mathml("√x"), "<msqrt><mi>x</mi></msqrt>");
+    assert_eq!(mathml("sqrt(2)"), "<msqrt><mn>2</mn></msqrt>");
+}
+
+#[test]
+fn functions_and_names() {
+    assert_eq!(
+        mathml("sin(x)"),
+        "<mrow><mi>sin</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>"
+    );
+    assert_eq!(
+        mathml("atan(x, y)"),
+        "<mrow><mi>arctan</mi><mo>&#x2061;</mo>\
+         <mrow><mo>(</mo><mi>x</mi><mo>,</mo><mi>y</mi><mo>)</mo></mrow></mrow>"
+    );
+    assert_eq!(
+        mathml("abs(x)"),
+        "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>"
+    );
+    assert_eq!(
+        mathml("pi * theta"),
+        "<mrow><mi>π</mi><mo>⋅</mo><mi>θ</mi></mrow>"
+    );
+    assert_eq!(
+        mathml("[1, x]"),
+        "<mrow><mo>[</mo><mn>1</mn><mo>,</mo><mi>x</mi><mo>]</mo></mrow>"
+    );
+    assert_eq!(
+        mathml("(a, b) => a"),
+        "<mrow><mrow><mo>(</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>)</mo></mrow><mo>↦</mo><mi>a</mi></mrow>"
+    );
+    assert_eq!(
+        mathml("let r = 2 in r"),
+        "<mrow><mtext>let</mtext><mi>r</mi><mo>=</mo><mn>2</mn><mtext>in</mtext><mi>r</mi></mrow>"
+    );
+
+    // names are escaped, whatever built the tree.
+    let ast = AST::Var("a<b".to_string());
+    assert_eq!(strip(&to_mathml(&ast)), "<mi>a&lt;b</mi>");
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(mathml("2.5"), "<mn>2.5</mn>");
+    assert_eq!(mathml("1e300"), "<msup><mn>10</mn><mn>300</mn></msup>");
+    assert_eq!(
+        mathml("1.5e-9"),
+        "<mrow><mn>1.5</mn><mo>×</mo><msup><mn>10</mn><mrow><mo>−</mo><mn>9</mn></mrow></msup></mrow>"
+    );
+    assert_eq!(mathml("1e999"), "<mi>∞</mi>");
+    assert_eq!(
+        strip(&to_mathml(&AST::Literal(-6.0))),
+        "<mrow><mo>−</mo><mn>6</mn></mrow>"
+    );
+}
+
+#[test]
+fn deep_trees() {
+    assert!(strip(&to_mathml(&deep_unary(Root))).starts_with("<msqrt><msqrt>"));
+}
@@ -0,0 +1,135 @@
+use std::{cell::RefCell, rc::Rc};
+
+use expr_solver::{
+    ast::{BinaryOperator::*, AST},
+    interpreter::{EvalError, Interpreter},
+    observer::Observer,
+    parse,
+    printer::Printer,
+    trace::Operation,
+    value::Value,
+};
+
+// writes down every event, nodes as they print.
+#[derive(Default)]
+struct Events(Vec<String>);
+
+impl Observer for Events {
+    fn enter(&mut self, ast: &AST) {
+        self.0.push(format!("enter {}", Printer::new().print(ast)));
+    }
+
+    fn leave(&mut self, ast: &AST, value: &Value) {
+        self.0
+            .push(format!("leave {} = {value}", Printer::new().print(ast)));
+    }
+
+    fn apply(&mut self, _ast: &AST, operation: &Operation, operands: &[Value], result: &Value) {
+        let operands: Vec<String> = operands.iter().map(Value::to_string).collect();
+        self.0.push(format!(
+            "apply {operation:?} [{}] = {result}",
+            operands.join(", ")
+        ));
+    }
+}
+
+// runs a program with an observer writing down events.
+fn observe(input: &str) -> (Result<Value, EvalError>, Vec<String>) {
+    let events = Rc::new(RefCell::new(Events::default()));
+    let mut interpreter = Interpreter::new().with_observer(events.clone());
+    let result = interpreter.run(&parse(input).unwrap());
+    let events = events.borrow().0.clone();
+    (result, events)
+}
+
+#[test]
+fn order_of_events() {
+    let (_, events) = observe("1 + 2 * 3");
+    assert_eq!(events.len(), 12);
+
+    let (_, events) = observe("-(1 + 2)");
+    assert_eq!(
+        events,
+        [
+            "enter -(1 + 2)",
+            "enter 1 + 2",
+            "enter 1",
+            "leave 1 = 1",
+            "enter 2",
+            "leave 2 = 2",
+            "apply Binary(Add) [1, 2] = 3",
+            "leave 1 + 2 = 3",
+            "apply Unary(Negate) [3] = -3",
+            "leave -(1 + 2) = -3",
+        ]
+    );
+}
+
+#[test]
+fn bindings_and_functions() {
+    let (_, events) = observe("f(x) = x!; let y = 2 in f(y)");
+    assert_eq!(
+        events,
+        [
+            "enter let y = 2 in f(y)",
+            "enter 2",
+            "leave 2 = 2",
+            "enter f(y)",
+            "enter y",
+            "leave y = 2",
+            // the body of the function is observed too.
+            "enter x!",
+            "enter x",
+            "leave x = 2",
+            "apply Postfix(Factorial) [2] = 2",
+            "leave x! = 2",
+            "apply Call(\"f\") [2] = 2",
+            "leave f(y) = 2",
+            "leave let y = 2 in f(y) = 2",
+        ]
+    );
+
+    let (_, events) = observe("[1, x => x]");
+    assert_eq!(
+        events.last().unwrap(),
+        "leave [1, x => x] = [1, <function(x)>]"
+    );
+}
+
+#[test]
+fn failed_nodes_are_not_left() {
+    let (result, events) = observe("1 + y");
+    assert!(result.is_err());
+    assert_eq!(events, ["enter 1 + y", "enter 1", "leave 1 = 1", "enter y"]);
+}
+
+#[test]
+fn coverage() {
+    // which comparisons of a rule were evaluated, and to what.
+    #[derive(Default)]
+    struct Comparisons(Vec<(String, bool)>);
+
+    impl Observer for Comparisons {
+        fn apply(&mut self, ast: &AST, operation: &Operation, _: &[Value], result: &Value) {
+            if matches!(operation, Operation::Binary(Less | Greater | Equal)) {
+                let hit = result.as_number() == Ok(1.0);
+                self.0.push((Printer::new().print(ast), hit));
+            }
+        }
+    }
+
+    let comparisons = Rc::new(RefCell::new(Comparisons::default()));
+    let mut interpreter = Interpreter::new().with_observer(comparisons.clone());
+    interpreter
+        .run(&parse("age = 30; (age > 18) * (age < 25) + (age == 30)").unwrap())
+        .unwrap();
+
+    assert_eq!(
+        comparisons.borrow().0,
+        [
+            ("age > 18".to_string(), true),
+            ("age < 25".to_string(), false),
+            ("age == 30".to_string(), true),
+        ]
+    );
+}
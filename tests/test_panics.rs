@@ -0,0 +1,138 @@
+use expr_solver::{
+    ast::{UnaryOperator, AST},
+    interpreter::Interpreter,
+    parse, parse_with_options,
+    parser::ParserOptions,
+    resolve, resolve_script,
+};
+use proptest::prelude::*;
+
+// builds an ast of negations nested `depth` times around a number.
+fn nested_negations(depth: usize) -> AST {
+    let mut ast = AST::Literal(1.0);
+    for _ in 0..depth {
+        ast = AST::unary(UnaryOperator::Negate, ast);
+    }
+    ast
+}
+
+#[test]
+fn unexpected_tokens() {
+    for input in [
+        "*2", ")", "!2", "2+*3", ",", "=> 2", "in", "]", "2^", "((", "=", "",
+    ] {
+        assert!(resolve(input.to_string()).is_err(), "{input} should fail");
+    }
+
+    assert_eq!(
+        resolve("*2".to_string()),
+        Err("0..1: Unexpected token : *".to_string())
+    );
+}
+
+#[test]
+fn deep_nesting() {
+    let too_deep = Err("Expression is nested too deeply, maximum depth is 128.".to_string());
+    let message = |input: String| {
+        parse_with_options(&input, ParserOptions::default())
+            .map_err(|diagnostics| diagnostics[0].message.clone())
+            .map(|_| ())
+    };
+
+    assert_eq!(message("(".repeat(100_000)), too_deep);
+    assert_eq!(message("[".repeat(100_000)), too_deep);
+    assert_eq!(message("-".repeat(100_000) + "1"), too_deep);
+    assert_eq!(message("2^".repeat(100_000) + "2"), too_deep);
+    assert_eq!(message("f(".repeat(100_000)), too_deep);
+
+    // chains don't recurse in the parser, so they are limited only by memory.
+    assert_eq!(message("1".to_string() + &"+1".repeat(100_000)), Ok(()));
+    assert_eq!(message("1".to_string() + &"!".repeat(100_000)), Ok(()));
+
+    // anything under the limit is fine.
+    assert_eq!(resolve("(".repeat(100) + "1" + &")".repeat(100)), Ok(1.0));
+    assert_eq!(resolve("1".to_string() + &"+1".repeat(100)), Ok(101.0));
+
+    // the limit is configurable.
+    let options = ParserOptions {
+        max_depth: 4,
+        ..Default::default()
+    };
+    assert!(parse_with_options("((1))", options).is_ok());
+    assert!(parse_with_options("((((1))))", options).is_err());
+    assert!(parse_with_options("1+1+1+1+1", options).is_ok());
+}
+
+#[test]
+fn deep_evaluation() {
+    // evaluation is unlimited by default, but a limit can be set.
+    let mut interpreter = Interpreter::new().with_max_depth(3);
+    assert!(interpreter.evaluate(&nested_negations(2)).is_ok());
+    assert_eq!(
+        interpreter
+            .evaluate(&nested_negations(3))
+            .unwrap_err()
+            .to_string(),
+        "Expression is nested too deeply, maximum depth is 3."
+    );
+
+    // expressions in the body of a function are nested inside the call.
+    let mut interpreter = Interpreter::new().with_max_depth(3);
+    let statements = parse("f(x) = -x; f(1); -f(1)").unwrap();
+    assert!(interpreter.run_script(&statements[..2]).is_ok());
+    assert!(interpreter.run_script(&statements[2..]).is_err());
+
+    // infinite recursion still stops at the maximum call depth.
+    assert!(resolve("f(x) = f(x) + 1; f(1)".to_string()).is_err());
+}
+
+#[test]
+fn huge_factorials() {
+    assert_eq!(resolve("1e9!".to_string()), Ok(f64::INFINITY));
+    assert_eq!(resolve("170!".to_string()).map(f64::is_finite), Ok(true));
+}
+
+#[test]
+fn deep_values() {
+    // values nest as deep as the program builds them, without a limit.
+    let depth = 200_000;
+    let zeros = vec!["0"; depth].join(", ");
+
+    let lists = format!("reduce((a, b) => [a], [{zeros}], 0)");
+    assert_eq!(
+        resolve(lists.clone()),
+        Err("Expected a number, found a list.".to_string())
+    );
+    let values = resolve_script(lists).unwrap();
+    assert_eq!(
+        values[0].to_string(),
+        "[".repeat(depth) + "0" + &"]".repeat(depth)
+    );
+    assert_eq!(
+        format!("{:?}", values[0]),
+        "List([".repeat(depth) + "Number(0.0)" + &"])".repeat(depth)
+    );
+
+    let closures = format!("reduce((f, b) => (x => f(x)), [{zeros}], (x => x))");
+    assert_eq!(
+        resolve(closures.clone()),
+        Err("Expected a number, found a function.".to_string())
+    );
+    let values = resolve_script(closures).unwrap();
+    assert_eq!(values[0].to_string(), "<function(x)>");
+    assert!(format!("{:?}", values[0]).starts_with("Function(Function { parameters: [\"x\"]"));
+}
+
+proptest! {
+    #[test]
+    fn random_strings_never_panic(input in "\\PC*") {
+        let _ = resolve(input.clone());
+        let _ = resolve_script(input);
+    }
+
+    #[test]
+    fn random_expressions_never_panic(input in "[0-9a-z .,;()\\[\\]+*/^!=<>√π−-]{0,64}") {
+        let _ = resolve(input.clone());
+        let _ = resolve_script(input);
+    }
+}
@@ -0,0 +1,162 @@
+use expr_solver::{
+    ast::AST,
+    diagnostic::Diagnostic,
+    interpreter::Interpreter,
+    lexer::LexError,
+    lexer::Lexer,
+    parse_prefix,
+    parser::Parser,
+    parser::ParserOptions,
+    token::{Span, SpannedToken, Token},
+};
+
+// wraps tokens, as if they came from a custom token source.
+fn spanned(tokens: Vec<Token<'_>>) -> Vec<Result<SpannedToken<'_>, LexError>> {
+    tokens
+        .into_iter()
+        .map(|token| {
+            Ok(SpannedToken {
+                token,
+                span: Span::default(),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn custom_token_source() {
+    let tokens = spanned(vec![
+        Token::Number(2.0),
+        Token::Plus,
+        Token::Number(3.0),
+        Token::Star,
+        Token::Number(4.0),
+    ]);
+
+    let ast = Parser::new(tokens.into_iter()).parse().unwrap();
+    assert_eq!(Interpreter::walk_ast(&ast), Ok(14.0));
+
+    // sources aren't asked for more tokens once they ran out.
+    let mut tokens = spanned(vec![Token::Number(2.0)]).into_iter();
+    let mut ended = false;
+    let source = std::iter::from_fn(move || {
+        assert!(!ended, "token source polled after it ran out");
+        let token = tokens.next();
+        ended = token.is_none();
+        token
+    });
+    assert_eq!(Parser::new(source).parse(), Ok(AST::Literal(2.0)));
+}
+
+#[test]
+fn lexical_errors_stop_parsing() {
+    let tokens = vec![
+        Ok(SpannedToken {
+            token: Token::Number(2.0),
+            span: Span { start: 0, end: 1 },
+        }),
+        Err(LexError {
+            message: "Unexpected character '&'.".to_string(),
+            span: Span { start: 1, end: 2 },
+        }),
+    ];
+
+    let result = Parser::new(tokens.into_iter()).parse();
+    let error = result.unwrap_err();
+    assert_eq!(error.message, "Unexpected character '&'.");
+    assert_eq!(error.span, Span { start: 1, end: 2 });
+}
+
+// parses a program, keeping only the errors.
+fn parse_errors(input: &str) -> Vec<Diagnostic> {
+    Parser::new(Lexer::new(input))
+        .parse_program()
+        .err()
+        .unwrap_or_default()
+}
+
+#[test]
+fn every_lexical_error_is_reported() {
+    let diagnostics = parse_errors("1 & 2 @ 3 # 0xZ");
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+    assert_eq!(
+        messages,
+        [
+            "Unexpected character '&'.",
+            "Unexpected character '@'.",
+            "Unexpected character '#'.",
+            "Invalid digit 'Z' in hexadecimal literal '0xZ'.",
+        ]
+    );
+    assert_eq!(diagnostics[3].span, Span { start: 12, end: 15 });
+}
+
+#[test]
+fn parser_synchronizes_on_statements() {
+    let diagnostics = parse_errors("let = 1; 2 + 2\nf(1 2); (1, 2)\n3 ~");
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+    assert_eq!(
+        messages,
+        [
+            "Expected name after 'let'.",
+            "Expected operator recieved number : 2",
+            "Expected '=>' after parameter list.",
+            "Unexpected character '~'.",
+        ]
+    );
+    assert_eq!(diagnostics[0].span, Span { start: 4, end: 5 });
+
+    // a separator consumed by the failing statement doesn't swallow the next one.
+    let diagnostics = parse_errors("f(1;let = 2");
+    assert_eq!(diagnostics.len(), 2);
+}
+
+#[test]
+fn valid_programs_have_no_diagnostics() {
+    assert!(parse_errors("x = 1; f(y) = y * x\n\nf(2);").is_empty());
+}
+
+#[test]
+fn trailing_tokens_are_rejected() {
+    let diagnostics = parse_errors("2)2; 3 (4\n1 + 1");
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Unexpected token after end of expression : )",
+            "Unexpected token after end of expression : (",
+        ]
+    );
+    assert_eq!(diagnostics[0].span, Span { start: 1, end: 2 });
+    assert_eq!(diagnostics[1].span, Span { start: 7, end: 8 });
+
+    let error = Parser::new(Lexer::new("2 + 2 3")).parse().unwrap_err();
+    assert_eq!(error.message, "Expected operator recieved number : 3");
+
+    let error = Parser::new(Lexer::new("2 + 2 x")).parse().unwrap_err();
+    assert_eq!(error.span, Span { start: 6, end: 7 });
+}
+
+#[test]
+fn prefix_parsing() {
+    let prefix = |input: &str| parse_prefix(input, ParserOptions::default());
+
+    let (ast, consumed) = prefix("  2^3 is eight").unwrap();
+    assert_eq!(consumed, 5);
+    assert_eq!(Interpreter::walk_ast(&ast), Ok(8.0));
+
+    // invalid text ends the expression too.
+    assert_eq!(prefix("(1 + 2)} and more").unwrap().1, 7);
+    assert_eq!(prefix("1 = 2").unwrap().1, 1);
+
+    // so does a number after a complete expression.
+    let (ast, consumed) = prefix("3 4 apples").unwrap();
+    assert_eq!((ast, consumed), (AST::Literal(3.0), 1));
+    assert_eq!(prefix("2 * 3 7").unwrap().1, 5);
+
+    // but the expression itself must be complete.
+    assert!(prefix("1 + } 2").is_err());
+    assert!(prefix("").is_err());
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{ast, deep_binary, parse_expression, DEEP};
+use expr_solver::{
+    ast::{BinaryOperator::*, Statement, AST},
+    parse,
+    printer::{PrintOptions, Printer, Spacing},
+};
+use proptest::prelude::*;
+
+const COMPACT: PrintOptions = PrintOptions {
+    spacing: Spacing::Compact,
+};
+
+// parses an expression and prints it back.
+fn reprint(input: &str) -> String {
+    Printer::new().print(&parse_expression(input))
+}
+
+#[test]
+fn minimal_parentheses() {
+    assert_eq!(reprint("2 + 3 * 4"), "2 + 3 * 4");
+    assert_eq!(reprint("(2 + 3) * 4"), "(2 + 3) * 4");
+    assert_eq!(reprint("((2)) + ((3 * 4))"), "2 + 3 * 4");
+
+    // left associative operators only need them on the right.
+    assert_eq!(reprint("(1 - 2) - 3"), "1 - 2 - 3");
+    assert_eq!(reprint("1 - (2 - 3)"), "1 - (2 - 3)");
+    assert_eq!(reprint("1 / (2 * 3)"), "1 / (2 * 3)");
+
+    // '^' is right associative.
+    assert_eq!(reprint("2 ^ (3 ^ 4)"), "2 ^ 3 ^ 4");
+    assert_eq!(reprint("(2 ^ 3) ^ 4"), "(2 ^ 3) ^ 4");
+
+    // unary minus binds looser than '^' and '!'.
+    assert_eq!(reprint("-(2 ^ 2)"), "-2 ^ 2");
+    assert_eq!(reprint("(-2) ^ 2"), "(-2) ^ 2");
+    assert_eq!(reprint("(-2)!"), "(-2)!");
+    assert_eq!(reprint("-(2 + 3)"), "-(2 + 3)");
+    assert_eq!(reprint("2 * (-3)"), "2 * -3");
+    assert_eq!(reprint("(2 + 3)!"), "(2 + 3)!");
+
+    // comparisons bind loosest.
+    assert_eq!(reprint("(1 < 2) == (3 + 1 > 2)"), "1 < 2 == (3 + 1 > 2)");
+}
+
+#[test]
+fn bindings_and_functions() {
+    assert_eq!(reprint("f(1, (2), g())"), "f(1, 2, g())");
+    assert_eq!(reprint("[1, (2 + 3), []]"), "[1, 2 + 3, []]");
+    assert_eq!(reprint("map(x => (x * 2), [1])"), "map(x => x * 2, [1])");
+    assert_eq!(reprint("((a, b) => a + b)"), "(a, b) => a + b");
+    assert_eq!(reprint("(() => 1)"), "() => 1");
+
+    // bodies take everything after them, so they are wrapped unless nothing follows.
+    assert_eq!(reprint("let x = 1 in (x + 1)"), "let x = 1 in x + 1");
+    assert_eq!(reprint("(let x = 1 in x) + 1"), "(let x = 1 in x) + 1");
+    assert_eq!(reprint("1 + (let x = 1 in x)"), "1 + let x = 1 in x");
+    assert_eq!(
+        reprint("1 + (let x = 1 in x) * 2"),
+        "1 + (let x = 1 in x) * 2"
+    );
+    assert_eq!(reprint("-(x => x)"), "-x => x");
+    assert_eq!(reprint("(x => x)!"), "(x => x)!");
+
+    let statements = parse("f(x, y) = (x * y); z = (2)").unwrap();
+    let printer = Printer::new();
+    assert_eq!(printer.print_statement(&statements[0]), "f(x, y) = x * y");
+    assert_eq!(printer.print_statement(&statements[1]), "z = 2");
+}
+
+#[test]
+fn spacing() {
+    let compact = Printer::with_options(COMPACT);
+    assert_eq!(
+        compact.print(&parse_expression("2 + f(1, 2) * -x")),
+        "2+f(1,2)*-x"
+    );
+    assert_eq!(
+        compact.print(&parse_expression("let x = 1 in x")),
+        "let x=1 in x"
+    );
+    assert_eq!(compact.print(&parse_expression("(a, b) => a")), "(a,b)=>a");
+
+    // `3!==6` would lex as `3 != = 6`.
+    assert_eq!(compact.print(&parse_expression("3! == 6")), "3! ==6");
+}
+
+#[test]
+fn numbers() {
+    let print = |n: f64| Printer::new().print(&AST::Literal(n));
+    assert_eq!(print(2.0), "2");
+    assert_eq!(print(0.1), "0.1");
+    assert_eq!(print(1e300), "1e300");
+    assert_eq!(print(1.5e-9), "1.5e-9");
+    assert_eq!(print(f64::INFINITY), "1e999");
+
+    // negative literals come from folding, they are printed as a negation.
+    let ast = AST::binary(Power, AST::Literal(-2.0), AST::Literal(2.0));
+    assert_eq!(Printer::new().print(&ast), "(-2) ^ 2");
+}
+
+#[test]
+fn deep_trees() {
+    assert_eq!(
+        Printer::new().print(&deep_binary(Subtract)),
+        "1 - (".repeat(DEEP - 1) + "1 - 1" + &")".repeat(DEEP - 1)
+    );
+}
+
+proptest! {
+    #[test]
+    fn printed_trees_parse_back(ast in ast()) {
+        for printer in [Printer::new(), Printer::with_options(COMPACT)] {
+            let printed = printer.print(&ast);
+            let parsed = parse(&printed).map(|mut statements| statements.remove(0));
+            prop_assert_eq!(parsed, Ok(Statement::Expression(ast.clone())), "{}", printed);
+        }
+    }
+}
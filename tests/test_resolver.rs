@@ -1,4 +1,7 @@
-use expr_solver::resolve;
+use expr_solver::parser::{ImplicitMultiplication, ParserOptions};
+use expr_solver::{
+    interpreter::Interpreter, parse, resolve, resolve_script, resolve_with_options, value::Value,
+};
 
 #[test]
 fn basic_resolution() {
@@ -6,10 +9,158 @@ fn basic_resolution() {
     assert_eq!(resolve("(3+3)*3".to_string()), Ok(18.0));
     assert_eq!(resolve("3!".to_string()), Ok(6.0));
     assert_eq!(resolve("-3!".to_string()), Ok(-6.0));
-    assert_eq!(resolve("3.2!".to_string()), Ok(7.756592718904097));
+    // gamma(4.2) = 7.7566895357931776...
+    let factorial = resolve("3.2!".to_string()).unwrap();
+    assert!((factorial - 7.756_689_535_793_177_6).abs() < 1e-13);
     assert_eq!(resolve("3--3!".to_string()), Ok(9.0));
     assert_eq!(resolve("-2".to_string()), Ok(-2.0));
     assert_eq!(resolve("2--2".to_string()), Ok(4.0));
     assert_eq!(resolve("2+2*2*2*2+2".to_string()), Ok(20.0));
     assert_eq!(resolve("-2/2+2*2".to_string()), Ok(3.0));
 }
+
+#[test]
+fn functions_and_bindings() {
+    assert_eq!(resolve("2^3".to_string()), Ok(8.0));
+    assert_eq!(resolve("2^3^2".to_string()), Ok(512.0));
+    assert_eq!(resolve("-2^2".to_string()), Ok(-4.0));
+    assert_eq!(resolve("f(x) = x^2 + 1; f(3) + f(4)".to_string()), Ok(27.0));
+    assert_eq!(
+        resolve("f(x, y) = x * y; g(x) = f(x, 2) + 1; g(3)".to_string()),
+        Ok(7.0)
+    );
+    assert_eq!(
+        resolve("let r = 5 in pi*r^2".to_string()),
+        Ok(std::f64::consts::PI * 25.0)
+    );
+    assert_eq!(
+        resolve("let x = 2 in let x = x * 3 in x + 1".to_string()),
+        Ok(7.0)
+    );
+    assert_eq!(resolve("sqrt(16) + abs(-2)".to_string()), Ok(6.0));
+}
+
+#[test]
+fn functions_and_bindings_errors() {
+    // parameters are lexically scoped, `y` is not visible inside `f`.
+    assert!(resolve("f(x) = x + y; let y = 2 in f(1)".to_string()).is_err());
+    assert!(resolve("f(x) = f(x) + 1; f(1)".to_string()).is_err());
+    assert!(resolve("f(x) = g(x); g(x) = f(x); f(1)".to_string()).is_err());
+    assert!(resolve("f(x) = x; f(1, 2)".to_string()).is_err());
+    assert!(resolve("f(x, x) = x; f(1, 2)".to_string()).is_err());
+    assert!(resolve("f(x) = x".to_string()).is_err());
+    assert!(resolve("unknown(1)".to_string()).is_err());
+
+    // bindings don't outlive a failed let expression.
+    let mut interpreter = Interpreter::new();
+    assert!(interpreter
+        .run_script(&parse("let a = 1 in a + b").unwrap())
+        .is_err());
+    assert!(interpreter.run_script(&parse("a").unwrap()).is_err());
+}
+
+#[test]
+fn lambdas_and_lists() {
+    assert_eq!(resolve("let f = x => x * 2 in f(4)".to_string()), Ok(8.0));
+    assert_eq!(
+        resolve("let f = (x, y) => x - y in f(4, 1)".to_string()),
+        Ok(3.0)
+    );
+    assert_eq!(resolve("let f = () => 7 in f()".to_string()), Ok(7.0));
+    assert_eq!(
+        resolve("reduce((a, b) => a + b, map(x => x^2, [1, 2, 3]))".to_string()),
+        Ok(14.0)
+    );
+    assert_eq!(
+        resolve("reduce((a, b) => a + b, filter(x => x > 2, [1, 2, 3, 4]), 10)".to_string()),
+        Ok(17.0)
+    );
+    // lambdas capture the variables visible where they were created.
+    assert_eq!(
+        resolve("add(n) = x => x + n; reduce((a, b) => a * b, map(add(1), [1, 2, 3]))".to_string()),
+        Ok(24.0)
+    );
+    assert_eq!(
+        resolve("square(x) = x * x; reduce((a, b) => a + b, map(square, [2, 3]))".to_string()),
+        Ok(13.0)
+    );
+    assert_eq!(resolve("1 + 1 == 2".to_string()), Ok(1.0));
+    assert_eq!(resolve("3 <= 2".to_string()), Ok(0.0));
+
+    assert!(resolve("[1, 2]".to_string()).is_err());
+    assert!(resolve("x => x".to_string()).is_err());
+    assert!(resolve("map(1, [1])".to_string()).is_err());
+    assert!(resolve("reduce((a, b) => a + b, [])".to_string()).is_err());
+    assert!(resolve("(1, 2)".to_string()).is_err());
+    assert!(resolve("(x, 1) => x".to_string()).is_err());
+}
+
+#[test]
+fn scripts() {
+    let values = resolve_script("x = 3\ny = x * 2; x + y\n\nans * 2;".to_string()).unwrap();
+    let numbers: Vec<f64> = values.iter().map(|v| v.as_number().unwrap()).collect();
+    assert_eq!(numbers, vec![3.0, 6.0, 9.0, 18.0]);
+
+    // definitions evaluate to the defined function.
+    let values = resolve_script("f(x) = x + 1; f(ans)".to_string());
+    assert!(values.is_err());
+    let values = resolve_script("1; f(x) = x + 1; f(ans)".to_string()).unwrap();
+    assert!(matches!(values[1], Value::Function(_)));
+    assert_eq!(values[2].as_number(), Ok(2.0));
+
+    assert_eq!(resolve("2 + 3; ans * 2".to_string()), Ok(10.0));
+    assert!(resolve("2 + 3 = 5".to_string()).is_err());
+
+    // assignments persist across runs of the same interpreter.
+    let mut interpreter = Interpreter::new();
+    interpreter.run_script(&parse("a = 4").unwrap()).unwrap();
+    let values = interpreter.run_script(&parse("a^2").unwrap()).unwrap();
+    assert_eq!(values[0].as_number(), Ok(16.0));
+}
+
+#[test]
+fn implicit_multiplication() {
+    let same = ParserOptions {
+        implicit_multiplication: ImplicitMultiplication::SameAsExplicit,
+        ..Default::default()
+    };
+    let tighter = ParserOptions {
+        implicit_multiplication: ImplicitMultiplication::Tighter,
+        ..Default::default()
+    };
+
+    for options in [same, tighter] {
+        let resolve = |input: &str| resolve_with_options(input.to_string(), options);
+        assert_eq!(resolve("2(3+4)"), Ok(14.0));
+        assert_eq!(resolve("let x = 5 in 3x"), Ok(15.0));
+        assert_eq!(resolve("let a = 3 in let b = 2 in (a+b)(a-b)"), Ok(5.0));
+        assert_eq!(resolve("2pi"), Ok(2.0 * std::f64::consts::PI));
+        assert_eq!(resolve("let x = 3 in 2x^2"), Ok(18.0));
+        assert_eq!(resolve("let x = 3 in 2x!"), Ok(12.0));
+        assert_eq!(resolve("let x = 3 in -2x + 1"), Ok(-5.0));
+        assert_eq!(resolve("f(x) = 2x; f(2)"), Ok(4.0));
+        assert_eq!(resolve("2e"), Ok(2.0 * std::f64::consts::E));
+        assert_eq!(resolve("2exp(0)"), Ok(2.0));
+        assert_eq!(resolve("2e3"), Ok(2000.0));
+        assert_eq!(resolve("2e-1"), Ok(0.2));
+        assert!(resolve("2 3").is_err());
+    }
+
+    assert_eq!(resolve_with_options("1/2(4)".to_string(), same), Ok(2.0));
+    assert_eq!(
+        resolve_with_options("1/2(4)".to_string(), tighter),
+        Ok(0.125)
+    );
+    assert_eq!(
+        resolve_with_options("6/2pi".to_string(), same),
+        Ok(3.0 * std::f64::consts::PI)
+    );
+}
+
+#[test]
+fn unicode_operators() {
+    assert_eq!(resolve("3×4÷2−1".to_string()), Ok(5.0));
+    assert_eq!(resolve("√16 + √(3^2)".to_string()), Ok(7.0));
+    assert_eq!(resolve("2×π".to_string()), Ok(2.0 * std::f64::consts::PI));
+    assert!(resolve("é+2".to_string()).is_err());
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::{ast, deep_binary};
+use expr_solver::{
+    ast::{BinaryOperator::*, Statement, AST},
+    diagnostic::Diagnostic,
+    interpreter::Interpreter,
+    lexer::Lexer,
+    parse,
+    token::{SpannedToken, Token},
+    value::Value,
+};
+use proptest::prelude::*;
+use serde_json::json;
+
+// serializes a value to json.
+fn to_json<T: serde::Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[test]
+fn tokens() {
+    let tokens: Vec<SpannedToken> = Lexer::new("x + 2").map(Result::unwrap).collect();
+    assert_eq!(
+        to_json(&tokens),
+        json!([
+            {"token": {"identifier": "x"}, "span": {"start": 0, "end": 1}},
+            {"token": "plus", "span": {"start": 2, "end": 3}},
+            {"token": {"number": 2.0}, "span": {"start": 4, "end": 5}},
+        ])
+    );
+
+    // names are borrowed from the json.
+    let json = serde_json::to_string(&tokens).unwrap();
+    let read: Vec<SpannedToken> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read, tokens);
+    assert_eq!(
+        serde_json::from_str::<Token>(r#""less_equal""#).unwrap(),
+        Token::LessEqual
+    );
+}
+
+#[test]
+fn trees() {
+    let statements = parse("2 * x + 1").unwrap();
+    assert_eq!(
+        to_json(&statements[0]),
+        json!({"expression": [
+            {"literal": 2.0},
+            {"var": "x"},
+            {"binary": "multiply"},
+            {"literal": 1.0},
+            {"binary": "add"},
+        ]})
+    );
+
+    let statements = parse("f(x, y) = let z = -x! in [z, g(y)]; h = (a) => a").unwrap();
+    assert_eq!(
+        to_json(&statements),
+        json!([
+            {"function": ["f", ["x", "y"], [
+                {"var": "x"},
+                {"postfix": "factorial"},
+                {"unary": "negate"},
+                {"var": "z"},
+                {"var": "y"},
+                {"call": {"name": "g", "arguments": 1}},
+                {"list": 2},
+                {"let": "z"},
+            ]]},
+            {"assignment": ["h", [{"var": "a"}, {"lambda": ["a"]}]]},
+        ])
+    );
+
+    let json = serde_json::to_string(&statements).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Vec<Statement>>(&json).unwrap(),
+        statements
+    );
+}
+
+#[test]
+fn invalid_trees() {
+    let read = |json| serde_json::from_str::<AST>(json).unwrap_err().to_string();
+    assert_eq!(
+        read(r#"[{"literal": 1.0}, {"binary": "add"}]"#),
+        "Expected 2 node(s) before the instruction, found 1."
+    );
+    assert_eq!(
+        read(r#"[{"literal": 1.0}, {"literal": 2.0}]"#),
+        "Expected instructions building one tree, they build 2."
+    );
+    assert_eq!(
+        read("[]"),
+        "Expected instructions building one tree, they build 0."
+    );
+}
+
+#[test]
+fn deep_trees() {
+    // far deeper than json's nesting limit, the tree is written as a flat list.
+    let chain = deep_binary(Add);
+    let json = serde_json::to_string(&chain).unwrap();
+    assert_eq!(serde_json::from_str::<AST>(&json).unwrap(), chain);
+}
+
+#[test]
+fn errors_and_values() {
+    let diagnostics = parse("2 +").unwrap_err();
+    assert_eq!(
+        to_json(&diagnostics),
+        json!([{"message": "Unexpected token : EOF", "span": {"start": 3, "end": 3}}])
+    );
+    let json = serde_json::to_string(&diagnostics).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Vec<Diagnostic>>(&json).unwrap(),
+        diagnostics
+    );
+
+    let mut interpreter = Interpreter::new();
+    let result = interpreter.run(&parse("y = 2; [1, y]").unwrap());
+    assert_eq!(
+        to_json(&result),
+        json!({"Ok": {"list": [{"number": 1.0}, {"number": 2.0}]}})
+    );
+
+    let result = interpreter.run(&parse("let k = 3 in x => x * k").unwrap());
+    assert_eq!(
+        to_json(&result),
+        json!({"Ok": {"function": {
+            "parameters": ["x"],
+            "body": [{"var": "x"}, {"var": "k"}, {"binary": "multiply"}],
+            "captured": {"k": {"number": 3.0}},
+        }}})
+    );
+
+    // a function read back keeps its body and captured variables.
+    let json = serde_json::to_string(&result.unwrap()).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+    let Value::Function(function) = &value else {
+        panic!("expected a function");
+    };
+    assert_eq!(function.body.to_string(), "( *   x   k )");
+    assert_eq!(function.captured["k"].as_number(), Ok(3.0));
+
+    let result = Interpreter::new().run(&parse("1 / [1]").unwrap());
+    assert!(to_json(&result)["Err"]["message"].is_string());
+
+    let result = Interpreter::new().run(&parse("f(x) = f(x); f(1)").unwrap());
+    assert_eq!(
+        to_json(&result)["Err"],
+        json!({"limit": {"call_depth": 256}})
+    );
+}
+
+proptest! {
+    #[test]
+    fn trees_round_trip(ast in ast()) {
+        let json = serde_json::to_string(&ast).unwrap();
+        prop_assert_eq!(serde_json::from_str::<AST>(&json).unwrap(), ast);
+    }
+}
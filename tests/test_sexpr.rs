@@ -0,0 +1,132 @@
+mod common;
+
+use common::{ast, deep_unary, parse_expression};
+use expr_solver::{
+    ast::{BinaryOperator::*, PostfixOperator::*, UnaryOperator::*, AST},
+    sexpr::read,
+};
+use proptest::prelude::*;
+
+// reads an s-expression, panicking on errors.
+fn tree(input: &str) -> AST {
+    read(input).unwrap_or_else(|diagnostic| panic!("{input} : {diagnostic}"))
+}
+
+// reads an s-expression, returning the error message.
+fn message(input: &str) -> String {
+    read(input).unwrap_err().to_string()
+}
+
+#[test]
+fn reading() {
+    let x = || AST::Var("x".to_string());
+
+    assert_eq!(tree("2"), AST::Literal(2.0));
+    assert_eq!(tree("-6.5"), AST::Literal(-6.5));
+    assert_eq!(tree("x"), x());
+    assert_eq!(tree("(- x)"), AST::unary(Negate, x()));
+    assert_eq!(
+        tree("(- x 1)"),
+        AST::binary(Subtract, x(), AST::Literal(1.0))
+    );
+    assert_eq!(tree("(+ x)"), AST::unary(Plus, x()));
+    assert_eq!(tree("(√ x)"), AST::unary(Root, x()));
+    assert_eq!(tree("(! x)"), AST::postfix(Factorial, x()));
+    assert_eq!(
+        tree("(<= x 1)"),
+        AST::binary(LessEqual, x(), AST::Literal(1.0))
+    );
+    assert_eq!(tree("(f)"), AST::Call("f".to_string(), vec![]));
+    assert_eq!(
+        tree("(f x [])"),
+        AST::Call("f".to_string(), vec![x(), AST::List(vec![])])
+    );
+    assert_eq!(tree("[1 x]"), AST::List(vec![AST::Literal(1.0), x()]));
+    assert_eq!(
+        tree("(let x 1 x)"),
+        AST::Let("x".to_string(), AST::Literal(1.0).into(), x().into())
+    );
+
+    // every name but the last is a parameter.
+    assert_eq!(tree("(=> x)"), AST::Lambda(vec![], x().into()));
+    assert_eq!(
+        tree("(=> x y x)"),
+        AST::Lambda(vec!["x".to_string(), "y".to_string()], x().into())
+    );
+
+    // special numbers are read the way they are printed.
+    assert_eq!(tree("#inf"), AST::Literal(f64::INFINITY));
+    assert_eq!(tree("-#inf"), AST::Literal(f64::NEG_INFINITY));
+    assert!(matches!(tree("#nan"), AST::Literal(n) if n.is_nan()));
+}
+
+#[test]
+fn special_numbers_round_trip() {
+    let special = AST::List(vec![
+        AST::Literal(f64::INFINITY),
+        AST::Literal(f64::NEG_INFINITY),
+    ]);
+    assert_eq!(special.to_string(), "[  #inf   -#inf  ]");
+    assert_eq!(tree(&special.to_string()), special);
+    assert_eq!(AST::Literal(f64::NAN).to_string(), "  #nan ");
+
+    // names spelled like special numbers stay names.
+    for input in ["let inf = 1 in inf", "NaN + 1", "-inf"] {
+        let ast = parse_expression(input);
+        assert_eq!(tree(&ast.to_string()), ast);
+    }
+}
+
+#[test]
+fn display_round_trip() {
+    let ast = &parse_expression("let y = 2 in map((a, b) => a * -b!, [1, y^2, f()]) <= √3");
+
+    assert_eq!(tree(&ast.to_string()), *ast);
+    assert_eq!(tree(&ast.to_string()).to_string(), ast.to_string());
+}
+
+#[test]
+fn errors() {
+    assert_eq!(message(""), "0..0: Expected expression.");
+    assert_eq!(message("(+ 1 2"), "0..1: Expected ')' to close this.");
+    assert_eq!(message("[1 2)"), "4..5: Expected ']' but found ')'.");
+    assert_eq!(message(")"), "0..1: Unexpected token : )");
+    assert_eq!(
+        message("1 2"),
+        "2..3: Unexpected token after end of expression."
+    );
+    assert_eq!(message("(* 1)"), "0..5: '*' doesn't take 1 operands.");
+    assert_eq!(message("(let 1 2 3)"), "5..6: Expected name after 'let'.");
+    assert_eq!(
+        message("(let x 1)"),
+        "0..9: Expected value and body in 'let x', found 1."
+    );
+    assert_eq!(
+        message("(=> 1 x)"),
+        "0..8: Expected parameter names in anonymous function."
+    );
+    assert_eq!(
+        message("(2 x)"),
+        "0..2: Expected operator or name after '(', found '2'."
+    );
+    assert_eq!(message("1.2.3"), "0..5: Invalid number literal '1.2.3'.");
+    assert_eq!(message("a$"), "0..2: Unexpected token : a$");
+}
+
+#[test]
+fn deep_trees() {
+    let ast = deep_unary(Negate);
+    assert_eq!(read(&ast.to_string()), Ok(ast));
+}
+
+proptest! {
+    #[test]
+    fn printed_trees_read_back(ast in ast()) {
+        prop_assert_eq!(read(&ast.to_string()), Ok(ast));
+    }
+
+    #[test]
+    fn random_input_never_panics(input in "[0-9a-z .()\\[\\]+*/^!=<>√-]{0,64}") {
+        let _ = read(&input);
+    }
+}
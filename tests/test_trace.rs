@@ -0,0 +1,144 @@
+mod common;
+
+use common::{deep_binary, parse_expression, DEEP};
+use expr_solver::{
+    ast::{BinaryOperator::*, PostfixOperator::*, UnaryOperator::*, AST},
+    interpreter::Interpreter,
+    parse,
+    trace::{rewrite, Operation, Step},
+    value::Value,
+};
+
+// evaluates the expression with a tracing interpreter, returning it and its steps.
+fn trace(input: &str) -> (AST, Vec<Step>) {
+    let ast = parse_expression(input);
+    let mut interpreter = Interpreter::new().with_trace();
+    interpreter.evaluate(&ast).unwrap();
+    (ast, interpreter.take_trace())
+}
+
+// renders the rewritten expressions of an evaluation.
+fn rewritten(input: &str) -> String {
+    let (ast, steps) = trace(input);
+    rewrite(&ast, &steps).join(" → ")
+}
+
+// returns the numbers in values.
+fn numbers(values: &[Value]) -> Vec<f64> {
+    values.iter().map(|v| v.as_number().unwrap()).collect()
+}
+
+#[test]
+fn steps() {
+    let (_, steps) = trace("-2 + 3! * 4");
+
+    let operations: Vec<&Operation> = steps.iter().map(|s| &s.operation).collect();
+    assert_eq!(
+        operations,
+        [
+            &Operation::Unary(Negate),
+            &Operation::Postfix(Factorial),
+            &Operation::Binary(Multiply),
+            &Operation::Binary(Add),
+        ]
+    );
+
+    let operands: Vec<Vec<f64>> = steps.iter().map(|s| numbers(&s.operands)).collect();
+    assert_eq!(
+        operands,
+        [vec![2.0], vec![3.0], vec![6.0, 4.0], vec![-2.0, 24.0]]
+    );
+
+    let results: Vec<f64> = steps
+        .iter()
+        .map(|s| s.result.as_number().unwrap())
+        .collect();
+    assert_eq!(results, [-2.0, 6.0, 24.0, 22.0]);
+
+    assert_eq!(
+        *steps[2].expression(),
+        AST::binary(
+            Multiply,
+            AST::postfix(Factorial, AST::Literal(3.0)),
+            AST::Literal(4.0)
+        )
+    );
+    assert_eq!(steps[2].to_string(), "3! * 4 = 24");
+    assert!(steps.iter().all(|s| s.depth == 0));
+}
+
+#[test]
+fn function_steps() {
+    let (_, steps) = trace("map(x => x ^ 2, [1, 2])");
+    let steps: Vec<(String, usize)> = steps.iter().map(|s| (s.to_string(), s.depth)).collect();
+    assert_eq!(
+        steps,
+        [
+            ("x ^ 2 = 1".to_string(), 1),
+            ("x ^ 2 = 4".to_string(), 1),
+            ("map(x => x ^ 2, [1, 2]) = [1, 4]".to_string(), 0),
+        ]
+    );
+
+    let (_, steps) = trace("sqrt(9)");
+    assert_eq!(steps[0].operation, Operation::Call("sqrt".to_string()));
+    assert_eq!(numbers(&steps[0].operands), [9.0]);
+}
+
+#[test]
+fn rewriting() {
+    assert_eq!(rewritten("2 + 3 * 4"), "2 + 3 * 4 → 2 + 12 → 14");
+    assert_eq!(
+        rewritten("(1 + 2) * (3 - 5)"),
+        "(1 + 2) * (3 - 5) → 3 * (3 - 5) → 3 * -2 → -6"
+    );
+    assert_eq!(rewritten("2 ^ -1"), "2 ^ -1 → 0.5");
+    assert_eq!(rewritten("7"), "7");
+
+    // calls show their result, not the steps inside them.
+    assert_eq!(
+        rewritten("abs(1 - 3) + reduce((a, b) => a * b, [2, 3])"),
+        "abs(1 - 3) + reduce((a, b) => a * b, [2, 3]) → abs(-2) + reduce((a, b) => a * b, [2, 3]) \
+         → 2 + reduce((a, b) => a * b, [2, 3]) → 2 + 6 → 8"
+    );
+
+    // lets and lists have no step of their own.
+    assert_eq!(
+        rewritten("let x = 1 + 1 in x * 3"),
+        "let x = 1 + 1 in x * 3 → let x = 2 in x * 3 → let x = 2 in 6"
+    );
+    assert_eq!(
+        rewritten("[1 + 1, map(x => x + 1, [1])]"),
+        "[1 + 1, map(x => x + 1, [1])] → [2, map(x => x + 1, [1])] → [2, [2]]"
+    );
+}
+
+#[test]
+fn tracing_is_opt_in() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&parse("1 + 2").unwrap()).unwrap();
+    assert!(interpreter.take_trace().is_empty());
+
+    // steps of failed evaluations are kept up to the failure.
+    let mut interpreter = Interpreter::new().with_trace();
+    assert!(interpreter.run(&parse("1 + 2 + [3]").unwrap()).is_err());
+    let steps: Vec<String> = interpreter
+        .take_trace()
+        .iter()
+        .map(Step::to_string)
+        .collect();
+    assert_eq!(steps, ["1 + 2 = 3"]);
+}
+
+#[test]
+fn long_traces() {
+    // steps share the tree, so tracing a long chain stays linear.
+    let mut interpreter = Interpreter::new().with_trace();
+    let result = interpreter.evaluate(&deep_binary(Add)).unwrap();
+    assert_eq!(result.as_number(), Ok(DEEP as f64 + 1.0));
+
+    let steps = interpreter.take_trace();
+    assert_eq!(steps.len(), DEEP);
+    assert_eq!(steps.last().unwrap().node, 0);
+    assert_eq!(steps[0].to_string(), "1 + 1 = 2");
+}
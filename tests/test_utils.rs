@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use expr_solver::{
+    interpreter::{FloatPolicy, Interpreter},
+    parse, resolve,
+    utils::{factorial, gamma, lgamma},
+};
+
+// checks a value against a high precision reference, relative to its size.
+fn assert_close(value: f64, expected: f64) {
+    let error = ((value - expected) / expected).abs();
+    assert!(
+        error < 1e-12,
+        "{value} isn't {expected}, relative error {error:e}"
+    );
+}
+
+#[test]
+fn gamma_values() {
+    // references computed to 20 significant digits, rounded to the nearest float.
+    let cases = [
+        (0.1, 9.513507698668732),
+        (0.5, 1.772453850905516),
+        (1.5, 0.886226925452758),
+        (3.0, 2.0),
+        (4.2, 7.7566895357931775),
+        (10.5, 1133278.3889487856),
+        (30.7, 9.528117499079501e31),
+        (100.5, 9.320963104082716e156),
+        (171.5, 9.4833675668248e307),
+        (1e-5, 99999.42279422557),
+        // negative numbers go through the reflection formula.
+        (-0.5, -3.544907701811032),
+        (-1.5, 2.363271801207355),
+        (-2.5, -0.9453087204829419),
+        (-10.3, -5.262363239535627e-7),
+    ];
+
+    for (x, expected) in cases {
+        assert_close(gamma(x), expected);
+    }
+}
+
+#[test]
+fn gamma_edges() {
+    assert_eq!(gamma(0.0), f64::INFINITY);
+    assert_eq!(gamma(-0.0), f64::NEG_INFINITY);
+    assert!(gamma(-1.0).is_nan());
+    assert!(gamma(-100.0).is_nan());
+    assert_eq!(gamma(172.0), f64::INFINITY);
+    assert_eq!(gamma(f64::INFINITY), f64::INFINITY);
+    assert!(gamma(f64::NAN).is_nan());
+
+    // integers are factorials.
+    let mut product = 1.0;
+    for n in 1..20 {
+        assert_close(gamma(n as f64 + 1.0), product * n as f64);
+        product *= n as f64;
+    }
+}
+
+#[test]
+fn lgamma_values() {
+    let cases = [
+        (0.5, 0.5723649429247001),
+        (-2.5, -0.056243716497674054),
+        (100.5, 361.4355404677776),
+        // ln(1000!), far beyond what gamma can hold.
+        (1001.0, 5912.128178488163),
+        (1e6, 12815504.569147611),
+        (1e300, 6.897755278982137e302),
+    ];
+
+    for (x, expected) in cases {
+        assert_close(lgamma(x), expected);
+    }
+
+    // lgamma agrees with gamma where both fit.
+    for x in [0.3, 2.7, 15.25, -3.7, 150.0] {
+        assert!((lgamma(x) - gamma(x).abs().ln()).abs() < 1e-12);
+    }
+
+    assert!(lgamma(1.0).abs() < 1e-15);
+    assert_eq!(lgamma(0.0), f64::INFINITY);
+    assert_eq!(lgamma(-4.0), f64::INFINITY);
+    assert_eq!(lgamma(f64::INFINITY), f64::INFINITY);
+}
+
+#[test]
+fn extended_factorials() {
+    // (-1/2)! = gamma(1/2) = sqrt(pi).
+    assert_close(factorial(-0.5), PI.sqrt());
+    assert_close(factorial(-2.5), 2.363271801207355);
+    assert_close(factorial(3.2), 7.7566895357931775);
+    assert!(factorial(-1.0).is_nan());
+
+    assert_close(resolve("(-0.5)!^2".to_string()).unwrap(), PI);
+    assert_eq!(
+        resolve("(-2)!".to_string()),
+        Err("Factorial of a negative integer in '(-2)!'.".to_string())
+    );
+
+    // large factorials in log space.
+    assert_close(
+        resolve("lgamma(1001) / ln(10)".to_string()).unwrap(),
+        2567.6046442221327,
+    );
+    let mut interpreter = Interpreter::new().with_float_policy(FloatPolicy::Strict);
+    assert_eq!(
+        interpreter
+            .run(&parse("lgamma(-1)").unwrap())
+            .unwrap_err()
+            .to_string(),
+        "Pole of gamma in 'lgamma(-1)'."
+    );
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::{deep_binary, parse_expression, DEEP};
+use expr_solver::{
+    ast::{BinaryOperator, Statement, UnaryOperator, AST},
+    interpreter::Interpreter,
+    parse,
+    visit::{self, preorder, Fold, Visitor},
+};
+
+// counts operators, and names which are not bound by a let or a lambda.
+#[derive(Default)]
+struct Analysis {
+    operators: usize,
+    bound: Vec<String>,
+    free: Vec<String>,
+}
+
+impl Visitor for Analysis {
+    fn visit_var(&mut self, name: &str) {
+        if !self.bound.iter().any(|bound| bound == name) {
+            self.free.push(name.to_string());
+        }
+    }
+
+    fn visit_unary(&mut self, _op: UnaryOperator, operand: &AST) {
+        self.operators += 1;
+        self.visit_ast(operand);
+    }
+
+    fn visit_binary(&mut self, _op: BinaryOperator, lhs: &AST, rhs: &AST) {
+        self.operators += 1;
+        self.visit_ast(lhs);
+        self.visit_ast(rhs);
+    }
+
+    fn visit_let(&mut self, name: &str, value: &AST, body: &AST) {
+        self.visit_ast(value);
+        self.bound.push(name.to_string());
+        self.visit_ast(body);
+        self.bound.pop();
+    }
+
+    fn visit_lambda(&mut self, parameters: &[String], body: &AST) {
+        self.bound.extend(parameters.iter().cloned());
+        self.visit_ast(body);
+        self.bound.truncate(self.bound.len() - parameters.len());
+    }
+}
+
+// evaluates operators whose operands are all numbers.
+struct ConstantFolding;
+
+impl Fold for ConstantFolding {
+    fn fold_ast(&mut self, ast: AST) -> AST {
+        let ast = visit::fold_ast(self, ast);
+        match &ast {
+            AST::Unary { operand, .. } | AST::Postfix { operand, .. }
+                if matches!(**operand, AST::Literal(_)) => {}
+            AST::Binary { lhs, rhs, .. }
+                if matches!((&**lhs, &**rhs), (AST::Literal(_), AST::Literal(_))) => {}
+            _ => return ast,
+        }
+
+        match Interpreter::walk_ast(&ast) {
+            Ok(value) => AST::Literal(value),
+            Err(_) => ast,
+        }
+    }
+}
+
+#[test]
+fn visitor() {
+    let mut analysis = Analysis::default();
+    analysis.visit_ast(&parse_expression(
+        "-a + let b = 2 * c in map(x => x * b + y, [1, z])",
+    ));
+
+    assert_eq!(analysis.operators, 5);
+    assert_eq!(analysis.free, ["a", "c", "y", "z"]);
+}
+
+#[test]
+fn visitor_over_statements() {
+    let mut analysis = Analysis::default();
+    for statement in parse("f(x) = x + k; n = 2; f(n)").unwrap() {
+        analysis.visit_statement(&statement);
+    }
+
+    // parameters of definitions are not tracked by the analysis.
+    assert_eq!(analysis.free, ["x", "k", "n"]);
+}
+
+#[test]
+fn fold() {
+    let ast = ConstantFolding.fold_ast(parse_expression("x * (2 + 3 * 4) - -(3!)"));
+    assert_eq!(ast.to_string(), "( - ( *   x   14 )  -6 )");
+
+    // nodes without constants are rebuilt as they were.
+    let input = "let a = [x, y] in map(v => v + a, a)";
+    let ast = ConstantFolding.fold_ast(parse_expression(input));
+    assert_eq!(ast.to_string(), parse_expression(input).to_string());
+
+    let statements: Vec<Statement> = parse("f(x) = x * (1 + 1); f(2 ^ 3)")
+        .unwrap()
+        .into_iter()
+        .map(|statement| ConstantFolding.fold_statement(statement))
+        .collect();
+    assert_eq!(statements[0].to_string(), "( = f(x) ( *   x   2 ))");
+    assert_eq!(statements[1].to_string(), "( f   8 )");
+}
+
+#[test]
+fn preorder_walk() {
+    let ast = parse_expression("let y = 2 in f(x, -y) + [z => z]");
+    let nodes: Vec<String> = preorder(&ast).map(|node| format!("{node:?}")).collect();
+    assert_eq!(nodes.len(), 10);
+    assert!(nodes[0].starts_with("Let(\"y\""));
+    assert_eq!(nodes[1], "Literal(2.0)");
+    assert_eq!(nodes[4], "Var(\"x\")");
+    assert_eq!(nodes[9], "Var(\"z\")");
+
+    assert_eq!(
+        preorder(&deep_binary(BinaryOperator::Add)).count(),
+        2 * DEEP + 1
+    );
+}